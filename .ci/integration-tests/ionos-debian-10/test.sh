@@ -1,7 +1,8 @@
 /stackable.sh testdriver-1 -i /.cluster/key 'sudo sh -c "echo \"13.32.25.75     static.rust-lang.org\" >> /etc/hosts"'
 /stackable.sh testdriver-1 -i /.cluster/key 'curl --proto "=https" --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y'
 /stackable.sh testdriver-1 -i /.cluster/key 'cargo --version'
-/stackable.sh testdriver-1 -i /.cluster/key 'sudo apt-get install gcc libssl-dev pkg-config git -y'
+/stackable.sh testdriver-1 -i /.cluster/key 'sudo apt-get install gcc libssl-dev pkg-config git python3 -y'
+/stackable.sh main-1 -i /.cluster/key 'sudo apt-get install python3 -y'
 /stackable.sh testdriver-1 -i /.cluster/key "git clone -b $GIT_BRANCH https://github.com/stackabletech/agent-integration-tests.git"
 /stackable.sh testdriver-1 -i /.cluster/key 'cd agent-integration-tests/ && cargo test'
 /stackable.sh main-1 -i /.cluster/key 'journalctl -u stackable-agent' > /target/stackable-agent.log
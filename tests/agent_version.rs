@@ -0,0 +1,126 @@
+mod util;
+
+use anyhow::Result;
+use rstest::rstest;
+
+use crate::util::agent::AgentVersion;
+
+#[rstest]
+#[case::release("0.6.0", 0, 6, 0, None)]
+#[case::v_prefix("v0.6.0", 0, 6, 0, None)]
+#[case::surrounding_whitespace(" 0.6.0\n", 0, 6, 0, None)]
+#[case::pre_release("0.7.0-nightly", 0, 7, 0, Some("nightly"))]
+#[case::dotted_pre_release("1.2.3-rc.10", 1, 2, 3, Some("rc.10"))]
+#[case::build_metadata("0.6.0+20211018", 0, 6, 0, None)]
+#[case::pre_release_and_build_metadata("0.7.0-rc.1+abc", 0, 7, 0, Some("rc.1"))]
+#[case::zero_identifier("0.7.0-0", 0, 7, 0, Some("0"))]
+#[case::alphanumeric_identifier_with_leading_zero("0.7.0-0a", 0, 7, 0, Some("0a"))]
+fn agent_version_should_be_parsed(
+    #[case] version: &str,
+    #[case] major: u64,
+    #[case] minor: u64,
+    #[case] patch: u64,
+    #[case] pre_release: Option<&str>,
+) -> Result<()> {
+    let expected = AgentVersion {
+        major,
+        minor,
+        patch,
+        pre_release: pre_release.map(String::from),
+    };
+
+    assert_eq!(expected, version.parse::<AgentVersion>()?);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::empty("")]
+#[case::missing_patch("0.6")]
+#[case::too_many_numbers("0.6.0.1")]
+#[case::not_a_number("0.six.0")]
+#[case::negative("0.-6.0")]
+#[case::double_v_prefix("vv0.6.0")]
+#[case::numeric_identifier_with_leading_zero("0.7.0-01")]
+#[case::dotted_numeric_identifier_with_leading_zero("0.7.0-rc.01")]
+fn invalid_agent_version_should_be_rejected(#[case] version: &str) {
+    assert!(version.parse::<AgentVersion>().is_err());
+}
+
+#[rstest]
+#[case::patch("0.6.0", "0.6.1")]
+#[case::minor("0.6.9", "0.7.0")]
+#[case::major("0.99.99", "1.0.0")]
+#[case::numeric_comparison("0.9.0", "0.10.0")]
+#[case::pre_release_before_release("0.7.0-nightly", "0.7.0")]
+#[case::pre_release_after_previous_release("0.6.0", "0.7.0-nightly")]
+#[case::alphanumeric_identifiers("0.7.0-alpha", "0.7.0-beta")]
+#[case::numeric_identifiers("0.7.0-rc.9", "0.7.0-rc.10")]
+#[case::numeric_before_alphanumeric_identifier("0.7.0-1", "0.7.0-alpha")]
+#[case::fewer_identifiers("0.7.0-alpha", "0.7.0-alpha.1")]
+#[case::semver_example_1("1.0.0-alpha.1", "1.0.0-alpha.beta")]
+#[case::semver_example_2("1.0.0-alpha.beta", "1.0.0-beta")]
+#[case::semver_example_3("1.0.0-beta", "1.0.0-beta.2")]
+#[case::semver_example_4("1.0.0-beta.2", "1.0.0-beta.11")]
+#[case::semver_example_5("1.0.0-beta.11", "1.0.0-rc.1")]
+#[case::semver_example_6("1.0.0-rc.1", "1.0.0")]
+fn agent_versions_should_be_ordered(#[case] lower: &str, #[case] higher: &str) -> Result<()> {
+    let lower = lower.parse::<AgentVersion>()?;
+    let higher = higher.parse::<AgentVersion>()?;
+
+    assert!(lower < higher, "{} < {} does not hold", lower, higher);
+    assert!(higher > lower, "{} > {} does not hold", higher, lower);
+
+    Ok(())
+}
+
+#[test]
+fn build_metadata_should_be_ignored_in_the_ordering() -> Result<()> {
+    let version = "0.6.0+build.1".parse::<AgentVersion>()?;
+    let other_version = "0.6.0+build.2".parse::<AgentVersion>()?;
+
+    assert_eq!(version, other_version);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::equal("0.6.0", "0.6.0", true)]
+#[case::equal_with_operator("0.6.0", "=0.6.0", true)]
+#[case::not_equal("0.6.1", "=0.6.0", false)]
+#[case::greater("0.7.0", ">0.6.0", true)]
+#[case::not_greater("0.6.0", ">0.6.0", false)]
+#[case::greater_or_equal("0.6.0", ">=0.6.0", true)]
+#[case::not_greater_or_equal("0.6.0-nightly", ">=0.6.0", false)]
+#[case::less("0.6.0-nightly", "<0.6.0", true)]
+#[case::not_less("0.6.0", "<0.6.0", false)]
+#[case::less_or_equal("0.6.0", "<=0.6.0", true)]
+#[case::not_less_or_equal("0.6.1", "<=0.6.0", false)]
+#[case::within_range("0.7.0", ">=0.6.0, <0.8.0", true)]
+#[case::below_range("0.5.0", ">=0.6.0, <0.8.0", false)]
+#[case::above_range("0.8.0", ">=0.6.0, <0.8.0", false)]
+#[case::whitespace_after_operator("0.7.0", ">= 0.6.0", true)]
+#[case::lowest_version("0.0.0-0", "<0.0.0-0", false)]
+fn agent_version_requirements_should_be_evaluated(
+    #[case] version: &str,
+    #[case] requirement: &str,
+    #[case] expected: bool,
+) -> Result<()> {
+    let version = version.parse::<AgentVersion>()?;
+
+    assert_eq!(expected, version.satisfies(requirement)?);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::invalid_version(">=0.6")]
+#[case::unknown_operator("~0.6.0")]
+#[case::empty_comparison(">=0.6.0,")]
+fn invalid_agent_version_requirements_should_be_rejected(#[case] requirement: &str) -> Result<()> {
+    let version = "0.6.0".parse::<AgentVersion>()?;
+
+    assert!(version.satisfies(requirement).is_err());
+
+    Ok(())
+}
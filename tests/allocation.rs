@@ -0,0 +1,212 @@
+mod util;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use integration_test_commons::test::prelude::*;
+
+use crate::util::fixture::{
+    close_repository, create_pod, delete_pod, start_repository, unique_name,
+};
+use crate::util::nodes::{free_pod_slots, require_node, stackable_node};
+use crate::util::result::TestResult;
+use crate::util::services::noop_service;
+use crate::util::status::{phase, verify_pod_condition, verify_status};
+
+/// Maximum number of pods which are started to fill a node
+///
+/// The test case fails instead of starting more pods to not overload
+/// the cluster.
+const MAX_FILLER_PODS: u32 = 250;
+
+/// Maximum number of filler pods which are created, awaited, or
+/// deleted concurrently
+const FILLER_POD_CONCURRENCY: usize = 10;
+
+/// Time in which the unschedulable pod must stay pending
+const PENDING_PERIOD: Duration = Duration::from_secs(10);
+
+/// Returns the reason of the PodScheduled condition if the pod is not
+/// scheduled.
+fn unschedulable_reason(pod: &Pod) -> Option<String> {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .and_then(|conditions| {
+            conditions
+                .iter()
+                .find(|condition| condition.type_ == "PodScheduled" && condition.status == "False")
+        })
+        .map(|condition| condition.reason.to_owned().unwrap_or_default())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pod_exceeding_the_allocatable_pods_should_stay_pending_until_a_pod_is_deleted(
+) -> Result<()> {
+    let mut client = KubeClient::new().await?;
+    client.timeouts.create = Duration::from_secs(60);
+    client.timeouts.delete = Duration::from_secs(60);
+    client.timeouts.verify_status = Duration::from_secs(120);
+
+    let mut result = TestResult::default();
+
+    let node = stackable_node(&client).await?;
+    let node_name = node
+        .metadata
+        .name
+        .to_owned()
+        .ok_or_else(|| anyhow!("The node has no name"))?;
+
+    let free_pod_slots = free_pod_slots(&node).await?;
+    if free_pod_slots > MAX_FILLER_PODS {
+        return Err(anyhow!(
+            "The node {} has {} free pod slots but at most {} pods are started to fill it",
+            node_name,
+            free_pod_slots,
+            MAX_FILLER_PODS
+        ));
+    }
+
+    let service = noop_service();
+    let repository_result = start_repository(&client, &mut result, &[&service]).await;
+
+    let pod_definition = |name: &str| {
+        let mut pod_definition = service.pod(&unique_name(name));
+        require_node(&mut pod_definition, &node_name);
+        pod_definition
+    };
+
+    let filler_pod_results = stream::iter(0..free_pod_slots)
+        .map(|_| {
+            let client = &client;
+            let pod_definition = pod_definition("agent-allocation-integration-test-filler");
+            async move {
+                let spec = serde_yaml::to_string(&pod_definition)?;
+                client.create::<Pod>(&spec).await
+            }
+        })
+        .buffer_unordered(FILLER_POD_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for filler_pod_result in filler_pod_results.iter() {
+        result.combine(filler_pod_result);
+    }
+    let mut filler_pods = filler_pod_results
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+
+    let ready_results = stream::iter(filler_pods.iter())
+        .map(|pod| verify_pod_condition(&client, pod, "Ready"))
+        .buffer_unordered(FILLER_POD_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for ready_result in ready_results.iter() {
+        result.combine(ready_result);
+    }
+
+    let pending_pod_result = create_pod(
+        &client,
+        &mut result,
+        &pod_definition("agent-allocation-integration-test-pending"),
+    )
+    .await;
+
+    if let (Ok(pending_pod), true) = (&pending_pod_result, result.is_ok()) {
+        let verify_pending_result = verify_pending(&client, pending_pod).await;
+        result.combine(&verify_pending_result);
+
+        if let Some(filler_pod) = filler_pods.pop() {
+            delete_pod(&client, &mut result, Ok(filler_pod)).await;
+        }
+
+        let pod_ready = verify_pod_condition(&client, pending_pod, "Ready")
+            .await
+            .map_err(|error| {
+                anyhow!(
+                    "The pending pod was not started after a pod was deleted on the node: {}",
+                    error
+                )
+            });
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pending_pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            result.check_eq(
+                "node of the formerly pending pod",
+                &Some(node_name.to_owned()),
+                &pod.spec.and_then(|spec| spec.node_name),
+            );
+        }
+    }
+
+    delete_pod(&client, &mut result, pending_pod_result).await;
+    let deletion_results = stream::iter(filler_pods)
+        .map(|pod| client.delete(pod))
+        .buffer_unordered(FILLER_POD_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for deletion_result in deletion_results.iter() {
+        result.combine(deletion_result);
+    }
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
+}
+
+/// Verifies that the given pod is not scheduled because the node is
+/// full and that it stays pending.
+async fn verify_pending(client: &KubeClient, pod: &Pod) -> Result<()> {
+    verify_status(client, pod, |pod| unschedulable_reason(pod).is_some())
+        .await
+        .map_err(|error| {
+            anyhow!(
+                "The pod exceeding the allocatable pods was not marked as unschedulable: {}",
+                error
+            )
+        })?;
+
+    tokio::time::sleep(PENDING_PERIOD).await;
+
+    let pod = client.get_status(pod).await?;
+
+    if phase(&pod) != "Pending" {
+        return Err(anyhow!(
+            "The pod exceeding the allocatable pods is in phase {} instead of Pending",
+            phase(&pod)
+        ));
+    }
+
+    let reason = unschedulable_reason(&pod);
+    if reason.as_deref() != Some("Unschedulable") {
+        return Err(anyhow!(
+            "The PodScheduled condition of the pod exceeding the allocatable pods has the \
+            reason {:?} instead of Unschedulable",
+            reason
+        ));
+    }
+
+    let message = pod
+        .status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .and_then(|conditions| {
+            conditions
+                .iter()
+                .find(|condition| condition.type_ == "PodScheduled")
+        })
+        .and_then(|condition| condition.message.to_owned())
+        .unwrap_or_default();
+    if !message.contains("Too many pods") {
+        return Err(anyhow!(
+            "The scheduling message of the pod exceeding the allocatable pods does not \
+            mention the pod limit: [{}]",
+            message
+        ));
+    }
+
+    Ok(())
+}
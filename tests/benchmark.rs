@@ -0,0 +1,194 @@
+mod util;
+
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use uuid::Uuid;
+
+use crate::util::benchmark::{
+    BenchmarkReport, PACKAGE_DOWNLOAD, PACKAGE_UNPACK, POD_DELETION, POD_READY, UNIT_START,
+};
+use crate::util::fixture::{close_repository, start_recording_repository, unique_name};
+use crate::util::probe::node_name;
+use crate::util::result::TestResult;
+use crate::util::scale::env_var;
+use crate::util::services::noop_service;
+use crate::util::status::{condition_status, verify_pod_condition};
+use crate::util::test_package::TestPackage;
+use crate::util::units::unit_timestamps;
+
+/// Environment variable which overrides the number of measured pod
+/// lifecycles
+const ITERATIONS_ENV: &str = "AGENT_BENCHMARK_ITERATIONS";
+
+/// Default number of measured pod lifecycles
+const DEFAULT_ITERATIONS: usize = 3;
+
+/// Interval in which the pod status is polled
+///
+/// The status is polled instead of awaited with the client because the
+/// time of the first ready observation is needed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum time until the pod must be ready
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Latencies of one pod lifecycle
+#[derive(Debug, Default)]
+struct Lifecycle {
+    package_download: Option<Duration>,
+    package_unpack: Option<Duration>,
+    unit_start: Option<Duration>,
+    pod_ready: Option<Duration>,
+    pod_deletion: Option<Duration>,
+}
+
+#[tokio::test]
+async fn latencies_of_the_pod_lifecycle_should_be_measured() -> Result<()> {
+    let mut client = KubeClient::new().await?;
+    client.timeouts.delete = Duration::from_secs(60);
+
+    let mut result = TestResult::default();
+
+    let iterations = env_var(ITERATIONS_ENV)?.unwrap_or(DEFAULT_ITERATIONS);
+    let mut benchmark_report = BenchmarkReport::new(&client, "pod-lifecycle").await?;
+
+    for _ in 0..iterations {
+        let lifecycle = measure_lifecycle(&client, &mut result).await;
+
+        // The unpacking cannot be measured if the clocks are not
+        // synchronized, so it is skipped then instead of failing.
+        if let Some(package_unpack) = lifecycle.package_unpack {
+            benchmark_report.record(PACKAGE_UNPACK, package_unpack);
+        }
+
+        let measurements = [
+            (PACKAGE_DOWNLOAD, lifecycle.package_download),
+            (UNIT_START, lifecycle.unit_start),
+            (POD_READY, lifecycle.pod_ready),
+            (POD_DELETION, lifecycle.pod_deletion),
+        ];
+        for (metric, latency) in measurements.iter() {
+            match latency {
+                Some(latency) => benchmark_report.record(*metric, *latency),
+                None => result.combine::<(), _>(&Err(format!(
+                    "The latency {} could not be measured",
+                    metric.name
+                ))),
+            }
+        }
+    }
+
+    result.combine(&benchmark_report.publish().await);
+
+    result.into()
+}
+
+/// Runs a pod with a newly versioned package, so that the agent must
+/// download and unpack it, and measures the latencies of its
+/// lifecycle.
+///
+/// The start of the systemd unit is taken from the clock of the node,
+/// so the clocks of the node and of the test driver must be
+/// synchronized to measure the unpacking. Otherwise the unpacking is
+/// not measured.
+async fn measure_lifecycle(client: &KubeClient, result: &mut TestResult) -> Lifecycle {
+    let mut lifecycle = Lifecycle::default();
+
+    let package = TestPackage {
+        version: format!("1.0.{}", Uuid::new_v4().as_u128() % 1_000_000_000),
+        ..noop_service()
+    };
+    let pod_name = unique_name("agent-benchmark-integration-test");
+    let pod_definition = package.pod(&pod_name);
+
+    let repository_result = start_recording_repository(client, result, &[&package]).await;
+
+    let creation_start = Instant::now();
+    let pod_result = client
+        .create::<Pod>(&serde_yaml::to_string(&pod_definition).unwrap())
+        .await;
+    result.combine(&pod_result);
+
+    if let Ok(pod) = pod_result {
+        let observation = observe_ready(client, &pod).await;
+        result.combine(&observation);
+
+        let pod_ready = verify_pod_condition(client, &pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        if let Ok((ready, ready_pod)) = observation {
+            lifecycle.pod_ready = Some(ready - creation_start);
+
+            let download = repository_result
+                .as_ref()
+                .ok()
+                .and_then(|repository| repository.downloads().into_iter().next());
+            let download_interval =
+                download.and_then(|download| download.end.map(|end| (download.start, end)));
+
+            lifecycle.package_download = download_interval.map(|(start, end)| end - start);
+
+            let unit = match node_name(&ready_pod) {
+                Some(node_name) => unit_timestamps(client, result, &node_name, &pod_name).await,
+                None => None,
+            };
+            let unit_started = unit.as_ref().and_then(|unit| unit.started());
+            let unit_activated = unit.as_ref().and_then(|unit| unit.activated());
+
+            if let (Some((_, download_end)), Some(unit_started)) = (download_interval, unit_started)
+            {
+                let download_end = SystemTime::now() - download_end.elapsed();
+                match unit_started.duration_since(download_end) {
+                    Ok(unpack) => lifecycle.package_unpack = Some(unpack),
+                    Err(_) => println!(
+                        "The latency {} is skipped because the systemd unit started before the \
+                        package download completed; the clocks of the node and the test driver \
+                        are probably not synchronized",
+                        PACKAGE_UNPACK.name
+                    ),
+                }
+            }
+
+            if let (Some(unit_started), Some(unit_activated)) = (unit_started, unit_activated) {
+                lifecycle.unit_start = unit_activated.duration_since(unit_started).ok();
+            }
+        }
+
+        let deletion_start = Instant::now();
+        let deletion_result = client.delete(pod).await;
+        result.combine(&deletion_result);
+        if deletion_result.is_ok() {
+            lifecycle.pod_deletion = Some(deletion_start.elapsed());
+        }
+    }
+
+    close_repository(client, result, repository_result).await;
+
+    lifecycle
+}
+
+/// Polls the status of the given pod until it is ready and returns the
+/// time when the pod was first observed ready together with its
+/// status.
+async fn observe_ready(client: &KubeClient, pod: &Pod) -> Result<(Instant, Pod)> {
+    let start = Instant::now();
+
+    loop {
+        let pod = client.get_status(pod).await?;
+        let now = Instant::now();
+
+        if condition_status(&pod, "Ready").as_deref() == Some("True") {
+            return Ok((now, pod));
+        }
+        if start.elapsed() > READY_TIMEOUT {
+            return Err(anyhow!(
+                "The pod did not become ready within {:?}",
+                READY_TIMEOUT
+            ));
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -2,9 +2,15 @@ mod util;
 
 use anyhow::Result;
 use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::core::v1::{EnvVar, EnvVarSource, ObjectFieldSelector};
+use rstest::rstest;
 
+use util::env_dump::run_env_dump;
+use util::fixture::unique_name;
 use util::repository::StackableRepositoryBuilder;
 use util::result::TestResult;
+use util::services::env_dump_job;
+use util::status::verify_status;
 use util::test_package::TestPackage;
 
 #[tokio::test]
@@ -45,12 +51,11 @@ async fn kubeconfig_should_be_set() -> Result<()> {
     // Verify that the job terminated successfully
 
     if let Ok(pod) = &pod_result {
-        let job_result = client
-            .verify_status::<Pod, _>(pod, |pod| {
-                let phase = pod.status.as_ref().and_then(|status| status.phase.as_ref());
-                phase == Some(&String::from("Succeeded"))
-            })
-            .await;
+        let job_result = verify_status(&client, pod, |pod| {
+            let phase = pod.status.as_ref().and_then(|status| status.phase.as_ref());
+            phase == Some(&String::from("Succeeded"))
+        })
+        .await;
         result.combine(&job_result);
     }
 
@@ -69,3 +74,220 @@ async fn kubeconfig_should_be_set() -> Result<()> {
 
     result.into()
 }
+
+#[tokio::test]
+async fn pod_environment_variables_should_be_set() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let variables = [
+        ("PLAIN_VALUE", "value"),
+        ("VALUE_WITH_SPACES", "value with spaces"),
+        ("EMPTY_VALUE", ""),
+    ];
+
+    let pod_definition = env_dump_job().pod_with_env(
+        &unique_name("agent-environment-integration-test-pod-env"),
+        &variables,
+    );
+
+    if let Some((env_dump, _)) = run_env_dump(&client, &mut result, &pod_definition).await {
+        for (name, value) in variables.iter() {
+            result.check_eq(
+                &format!("environment variable {}", name),
+                &Some(*value),
+                &env_dump.environment.get(*name).map(String::as_str),
+            );
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn downward_api_values_should_be_set() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let fields = [
+        ("POD_NAME", "metadata.name"),
+        ("POD_NAMESPACE", "metadata.namespace"),
+        ("POD_UID", "metadata.uid"),
+        ("NODE_NAME", "spec.nodeName"),
+        ("POD_IP", "status.podIP"),
+        ("HOST_IP", "status.hostIP"),
+    ];
+
+    let mut pod_definition = env_dump_job().pod(&unique_name(
+        "agent-environment-integration-test-downward-api",
+    ));
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        for container in spec.containers.iter_mut() {
+            container.env = Some(
+                fields
+                    .iter()
+                    .map(|(name, field_path)| EnvVar {
+                        name: String::from(*name),
+                        value_from: Some(EnvVarSource {
+                            field_ref: Some(ObjectFieldSelector {
+                                field_path: String::from(*field_path),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    if let Some((env_dump, pod)) = run_env_dump(&client, &mut result, &pod_definition).await {
+        let spec = pod.spec.unwrap_or_default();
+        let status = pod.status.unwrap_or_default();
+
+        let expected_values = [
+            pod.metadata.name,
+            pod.metadata.namespace,
+            pod.metadata.uid,
+            spec.node_name,
+            status.pod_ip,
+            status.host_ip,
+        ];
+
+        for ((name, field_path), expected_value) in fields.iter().zip(expected_values.iter()) {
+            result.check_eq(
+                &format!("environment variable {} referencing {}", name, field_path),
+                expected_value,
+                &env_dump.environment.get(*name).cloned(),
+            );
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn kubeconfig_should_be_readable_and_authenticate() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let pod_definition = env_dump_job().pod(&unique_name(
+        "agent-environment-integration-test-kubeconfig",
+    ));
+
+    if let Some((env_dump, _)) = run_env_dump(&client, &mut result, &pod_definition).await {
+        if !env_dump.environment.contains_key("KUBECONFIG") {
+            result.combine::<(), _>(&Err("The environment variable KUBECONFIG is not set"));
+        }
+
+        for (path, file) in env_dump.kubeconfig_files.iter() {
+            if !file.readable {
+                result.combine::<(), _>(&Err(format!(
+                    "The file [{}] referenced in the kubeconfig is not readable",
+                    path
+                )));
+            }
+        }
+
+        // Every authenticated user is allowed to review its own access
+        // whereas anonymous requests are rejected.
+        match &env_dump.kubeconfig_access_review {
+            Some(review) => result.check_eq(
+                &format!(
+                    "HTTP status of the access review sent with the kubeconfig (error: {:?})",
+                    review.error
+                ),
+                &Some(201),
+                &review.status,
+            ),
+            None => result.combine::<(), _>(&Err(format!(
+                "The kubeconfig could not be read: {}",
+                env_dump
+                    .kubeconfig_error
+                    .as_deref()
+                    .unwrap_or("unknown reason")
+            ))),
+        }
+    }
+
+    result.into()
+}
+
+/// Name of the environment variable used in the escaping tests
+const ROUND_TRIP_KEY: &str = "ROUND_TRIP_VALUE";
+
+/// The values are rendered into systemd unit files and must be escaped
+/// for the unit file syntax, the systemd specifiers, and the shell.
+#[rstest]
+#[case::plain_value(String::from("value"))]
+#[case::empty_value(String::new())]
+#[case::spaces(String::from("  leading and trailing spaces  "))]
+#[case::double_quotes(String::from(r#"say "hello" and "goodbye""#))]
+#[case::single_quotes(String::from("it's 'quoted'"))]
+#[case::unbalanced_quotes(String::from(r#"one " and one '"#))]
+#[case::dollar_signs(String::from("$HOME ${HOME} $$ $1 $"))]
+#[case::percent_specifiers(String::from("%n %N %h %i %% %"))]
+#[case::backslashes(String::from(r#"\ \\ \n \t \" \"#))]
+#[case::newlines(String::from("first line\nsecond line\n\nlast line\n"))]
+#[case::control_characters(String::from("tab\tcarriage return\rescape\x1b"))]
+#[case::shell_metacharacters(String::from("`id`; $(id) | & > < * ? ~ # !"))]
+#[case::unicode(String::from("äöü ß € 日本語 🦀"))]
+#[case::combined_escapes(String::from(r#"%h "$HOME" \%h \$HOME '\'"#))]
+#[case::value_close_to_the_size_limit("0123456789abcdef".repeat(7500))]
+#[tokio::test]
+async fn environment_variable_should_round_trip_byte_exactly(#[case] value: String) -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let pod_definition = env_dump_job().pod_with_env(
+        &unique_name("agent-environment-integration-test-escaping"),
+        &[(ROUND_TRIP_KEY, value.as_str())],
+    );
+
+    if let Some((env_dump, _)) = run_env_dump(&client, &mut result, &pod_definition).await {
+        match env_dump.environment.get(ROUND_TRIP_KEY) {
+            Some(actual_value) => check_round_trip(&mut result, &value, actual_value),
+            None => result.combine::<(), _>(&Err(format!(
+                "The environment variable {} is not set",
+                ROUND_TRIP_KEY
+            ))),
+        }
+    }
+
+    result.into()
+}
+
+/// Compares the given values byte by byte and reports the first
+/// difference instead of both values which can be very large.
+fn check_round_trip(result: &mut TestResult, expected: &str, actual: &str) {
+    const CONTEXT: usize = 20;
+
+    let expected = expected.as_bytes();
+    let actual = actual.as_bytes();
+
+    let first_difference = expected
+        .iter()
+        .zip(actual)
+        .position(|(expected_byte, actual_byte)| expected_byte != actual_byte)
+        .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())));
+
+    if let Some(position) = first_difference {
+        let excerpt = |bytes: &[u8]| {
+            let start = position.saturating_sub(CONTEXT);
+            let end = (position + CONTEXT).min(bytes.len());
+            String::from_utf8_lossy(&bytes[start.min(end)..end]).into_owned()
+        };
+
+        result.combine::<(), _>(&Err(format!(
+            "The value of {} did not round-trip; it differs at byte {}: \
+            [{:?}] was expected but [{:?}] was found (expected length: {}, actual length: {})",
+            ROUND_TRIP_KEY,
+            position,
+            excerpt(expected),
+            excerpt(actual),
+            expected.len(),
+            actual.len()
+        )));
+    }
+}
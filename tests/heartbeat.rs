@@ -0,0 +1,260 @@
+mod util;
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::coordination::v1::Lease;
+use k8s_openapi::chrono::{DateTime, Utc};
+use kube::api::Api;
+use kube::Client;
+
+use crate::util::nodes::{stackable_node_names, stackable_nodes};
+use crate::util::result::TestResult;
+
+/// Namespace which contains the leases of the nodes
+const NODE_LEASE_NAMESPACE: &str = "kube-node-lease";
+
+/// Period in which the leases and heartbeats are observed
+const OBSERVATION_PERIOD: Duration = Duration::from_secs(180);
+
+/// Interval in which the leases and node conditions are polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum interval between two heartbeats of a node
+///
+/// This is the default of `--node-monitor-grace-period` in the
+/// kube-controller-manager. A node is considered unhealthy and its pods
+/// are evicted eventually if neither its lease nor the heartbeat in its
+/// Ready condition was renewed within this period.
+const NODE_MONITOR_GRACE_PERIOD: Duration = Duration::from_secs(40);
+
+/// Tracks the renewals of a timestamp
+///
+/// The intervals are measured locally between the polls which observed
+/// a change, so that clock skew between the test host and the cluster
+/// does not matter.
+struct RenewalTracker {
+    description: String,
+    max_interval: Duration,
+    last_value: Option<DateTime<Utc>>,
+    last_change: Instant,
+    renewals: usize,
+}
+
+impl RenewalTracker {
+    fn new(description: &str, max_interval: Duration) -> RenewalTracker {
+        RenewalTracker {
+            description: String::from(description),
+            max_interval,
+            last_value: None,
+            last_change: Instant::now(),
+            renewals: 0,
+        }
+    }
+
+    /// Records the currently observed timestamp.
+    ///
+    /// An error is returned if the timestamp went backwards or if it was
+    /// not renewed within the maximum interval.
+    fn observe(&mut self, value: Option<DateTime<Utc>>) -> Result<()> {
+        let value = value.ok_or_else(|| anyhow!("The {} is not set", self.description))?;
+
+        match self.last_value {
+            Some(last_value) if value < last_value => {
+                return Err(anyhow!(
+                    "The {} went backwards from {} to {}",
+                    self.description,
+                    last_value,
+                    value
+                ));
+            }
+            Some(last_value) if value > last_value => {
+                self.renewals += 1;
+                self.last_change = Instant::now();
+            }
+            Some(_) => {}
+            None => self.last_change = Instant::now(),
+        }
+        self.last_value = Some(value);
+
+        // A renewal may have happened right after the previous poll.
+        let elapsed = self.last_change.elapsed();
+        if elapsed > self.max_interval + POLL_INTERVAL {
+            Err(anyhow!(
+                "The {} was not renewed for {:?} although it must be renewed within {:?}",
+                self.description,
+                elapsed,
+                self.max_interval
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verifies that a renewal was observed if the observation period
+    /// was long enough to expect one.
+    fn verify_renewed(&self, observation_period: Duration) -> Result<()> {
+        if self.renewals == 0 && observation_period > self.max_interval + POLL_INTERVAL {
+            Err(anyhow!(
+                "The {} was not renewed within {:?}",
+                self.description,
+                observation_period
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn node_leases_should_be_renewed_within_the_lease_duration() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let lease_api: Api<Lease> = Api::namespaced(Client::try_default().await?, NODE_LEASE_NAMESPACE);
+
+    let node_names = stackable_node_names(&client).await?;
+    let mut trackers = BTreeMap::new();
+
+    for node_name in node_names.iter() {
+        let lease = lease_api.get(node_name).await.map_err(|error| {
+            anyhow!(
+                "The lease of node {} could not be retrieved from namespace {}: {}",
+                node_name,
+                NODE_LEASE_NAMESPACE,
+                error
+            )
+        })?;
+        let spec = lease.spec.unwrap_or_default();
+
+        result.check_eq(
+            &format!("holder identity of the lease of node {}", node_name),
+            &Some(node_name.to_owned()),
+            &spec.holder_identity,
+        );
+
+        match spec.lease_duration_seconds {
+            Some(lease_duration_seconds) if lease_duration_seconds > 0 => {
+                trackers.insert(
+                    node_name.to_owned(),
+                    RenewalTracker::new(
+                        &format!("renew time of the lease of node {}", node_name),
+                        Duration::from_secs(lease_duration_seconds as u64),
+                    ),
+                );
+            }
+            lease_duration_seconds => result.combine::<(), _>(&Err(format!(
+                "The lease of node {} has an invalid duration: {:?}",
+                node_name, lease_duration_seconds
+            ))),
+        }
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < OBSERVATION_PERIOD && result.is_ok() {
+        for (node_name, tracker) in trackers.iter_mut() {
+            let observe_result = lease_api
+                .get(node_name)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|lease| {
+                    tracker.observe(
+                        lease
+                            .spec
+                            .and_then(|spec| spec.renew_time)
+                            .map(|renew_time| renew_time.0),
+                    )
+                });
+            result.combine(&observe_result);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    for tracker in trackers.values() {
+        result.combine(&tracker.verify_renewed(start.elapsed()));
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn node_heartbeats_should_be_renewed_within_the_grace_period() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let lease_api: Api<Lease> = Api::namespaced(Client::try_default().await?, NODE_LEASE_NAMESPACE);
+
+    let mut trackers = stackable_node_names(&client)
+        .await?
+        .into_iter()
+        .map(|node_name| {
+            let tracker = RenewalTracker::new(
+                &format!(
+                    "heartbeat of node {} in its lease or Ready condition",
+                    node_name
+                ),
+                NODE_MONITOR_GRACE_PERIOD,
+            );
+            (node_name, tracker)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let start = Instant::now();
+    while start.elapsed() < OBSERVATION_PERIOD && result.is_ok() {
+        let nodes_result = stackable_nodes(&client).await;
+        result.combine(&nodes_result);
+
+        if let Ok(nodes) = nodes_result {
+            for (node_name, tracker) in trackers.iter_mut() {
+                let ready_condition = nodes
+                    .iter()
+                    .find(|node| node.metadata.name.as_ref() == Some(node_name))
+                    .map(get_node_conditions)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|condition| condition.type_ == "Ready");
+
+                result.check_eq(
+                    &format!("status of the Ready condition of node {}", node_name),
+                    &Some("True"),
+                    &ready_condition
+                        .as_ref()
+                        .map(|condition| condition.status.as_str()),
+                );
+
+                let ready_heartbeat = ready_condition
+                    .and_then(|condition| condition.last_heartbeat_time)
+                    .map(|last_heartbeat_time| last_heartbeat_time.0);
+
+                // Like the kube-controller-manager, the most recent
+                // heartbeat counts, no matter if it was given by the
+                // lease or by the Ready condition. Agents without lease
+                // support only renew the Ready condition.
+                let lease = match lease_api.get(node_name).await {
+                    Ok(lease) => Some(lease),
+                    Err(kube::Error::Api(response)) if response.code == 404 => None,
+                    Err(error) => {
+                        result.combine::<(), _>(&Err(error));
+                        None
+                    }
+                };
+                let lease_renewal = lease
+                    .and_then(|lease| lease.spec)
+                    .and_then(|spec| spec.renew_time)
+                    .map(|renew_time| renew_time.0);
+
+                let observe_result = tracker.observe(ready_heartbeat.max(lease_renewal));
+                result.combine(&observe_result);
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    for tracker in trackers.values() {
+        result.combine(&tracker.verify_renewed(start.elapsed()));
+    }
+
+    result.into()
+}
@@ -0,0 +1,251 @@
+mod util;
+
+use anyhow::Result;
+use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::core::v1::ContainerState;
+use rstest::rstest;
+
+use crate::util::features::{feature_enabled, RESTART_COUNT};
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::result::TestResult;
+use crate::util::services::{marker_job, marker_service};
+use crate::util::status::{
+    condition_status, container_status, init_container_status, is_running, phase,
+    verify_pod_condition, verify_status,
+};
+use crate::util::test_package::env_vars;
+
+/// Creates a pod with the marker-service as main container and one
+/// marker-job init container for each of the given marker names and
+/// exit codes.
+fn marker_pod(pod_name: &str, marker_dir: &str, init_markers: &[(&str, i32)]) -> Pod {
+    let job = marker_job();
+    let service = marker_service();
+
+    let required_markers = init_markers
+        .iter()
+        .map(|(marker_name, _)| *marker_name)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut pod = service.pod(pod_name);
+
+    if let Some(spec) = pod.spec.as_mut() {
+        for container in spec.containers.iter_mut() {
+            container.env = Some(env_vars(&[
+                ("MARKER_DIR", marker_dir),
+                ("REQUIRED_MARKERS", required_markers.as_str()),
+            ]));
+        }
+
+        spec.init_containers = Some(
+            init_markers
+                .iter()
+                .map(|(marker_name, exit_code)| {
+                    let mut container = job.container();
+                    container.name = String::from(*marker_name);
+                    container.env = Some(env_vars(&[
+                        ("MARKER_DIR", marker_dir),
+                        ("MARKER_NAME", *marker_name),
+                        ("EXIT_CODE", exit_code.to_string().as_str()),
+                    ]));
+                    container
+                })
+                .collect(),
+        );
+    }
+
+    pod
+}
+
+#[tokio::test]
+async fn init_containers_should_run_sequentially_before_the_main_container() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let marker_dir = format!("/tmp/{}", unique_name("marker-job"));
+    let init_markers = [("init-1", 0), ("init-2", 0), ("init-3", 0)];
+
+    let mut pod_definition = marker_pod(
+        &unique_name("agent-init-containers-integration-test-order"),
+        &marker_dir,
+        &init_markers,
+    );
+    // The marker-service terminates if the init containers did not run
+    // in order. It must not be restarted because then it could find the
+    // markers in a valid state.
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        spec.restart_policy = Some(String::from("Never"));
+    }
+
+    let (repository_result, pod_result) = set_up(
+        &client,
+        &mut result,
+        &[&marker_job(), &marker_service()],
+        &pod_definition,
+    )
+    .await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_initialized = verify_pod_condition(&client, pod, "Initialized").await;
+        result.combine(&pod_initialized);
+
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            for (marker_name, _) in init_markers.iter() {
+                let terminated = init_container_status(&pod, marker_name)
+                    .and_then(|container_status| container_status.state.as_ref())
+                    .and_then(|state| state.terminated.as_ref());
+                result.check_eq(
+                    &format!("exit code of init container {}", marker_name),
+                    &Some(0),
+                    &terminated.map(|terminated| terminated.exit_code),
+                );
+                result.check_eq(
+                    &format!("termination reason of init container {}", marker_name),
+                    &Some("Completed"),
+                    &terminated.and_then(|terminated| terminated.reason.as_deref()),
+                );
+            }
+
+            let main_container = marker_service().name;
+            result.check_eq(
+                "running state of the main container",
+                &true,
+                &is_running(&pod, &main_container),
+            );
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[rstest]
+#[case::failing_init_container_should_fail_the_pod_on_restart_policy_never("Never")]
+#[case::failing_init_container_should_be_restarted_on_restart_policy_onfailure("OnFailure")]
+#[case::failing_init_container_should_be_restarted_on_restart_policy_always("Always")]
+#[tokio::test]
+async fn failing_init_container_should_block_the_pod(#[case] restart_policy: &str) -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let marker_dir = format!("/tmp/{}", unique_name("marker-job"));
+    let init_markers = [("init-1", 0), ("init-2", 1), ("init-3", 0)];
+
+    let mut pod_definition = marker_pod(
+        &unique_name("agent-init-containers-integration-test-failure"),
+        &marker_dir,
+        &init_markers,
+    );
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        spec.restart_policy = Some(String::from(restart_policy));
+    }
+
+    let (repository_result, pod_result) = set_up(
+        &client,
+        &mut result,
+        &[&marker_job(), &marker_service()],
+        &pod_definition,
+    )
+    .await;
+
+    if let Ok(pod) = &pod_result {
+        if restart_policy == "Never" {
+            let verify_status_result =
+                verify_status(&client, pod, |pod| phase(pod) == "Failed").await;
+            result.combine(&verify_status_result);
+        } else if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await {
+            let verify_status_result = verify_status(&client, pod, |pod| {
+                init_container_status(pod, "init-2")
+                    .filter(|container_status| container_status.restart_count >= 2)
+                    .is_some()
+            })
+            .await;
+            result.combine(&verify_status_result);
+        } else {
+            // Without restart counts at least the first failure of
+            // init-2 is awaited.
+            let verify_status_result = verify_status(&client, pod, |pod| {
+                init_container_status(pod, "init-2")
+                    .filter(|container_status| {
+                        let terminated = |state: &Option<ContainerState>| {
+                            state
+                                .as_ref()
+                                .and_then(|state| state.terminated.as_ref())
+                                .is_some()
+                        };
+                        terminated(&container_status.state)
+                            || terminated(&container_status.last_state)
+                    })
+                    .is_some()
+            })
+            .await;
+            result.combine(&verify_status_result);
+        }
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            let expected_phase = if restart_policy == "Never" {
+                "Failed"
+            } else {
+                "Pending"
+            };
+            result.check_eq("pod phase", expected_phase, phase(&pod).as_str());
+
+            result.check_eq(
+                "status of the Initialized condition",
+                &Some(String::from("False")),
+                &condition_status(&pod, "Initialized"),
+            );
+
+            result.check_eq(
+                "exit code of the last run of init container init-2",
+                &Some(1),
+                &init_container_status(&pod, "init-2")
+                    .and_then(|container_status| {
+                        let state = container_status.state.as_ref()?;
+                        let last_state = container_status.last_state.as_ref();
+                        state
+                            .terminated
+                            .as_ref()
+                            .or_else(|| last_state.and_then(|state| state.terminated.as_ref()))
+                    })
+                    .map(|terminated| terminated.exit_code),
+            );
+
+            let init_3_started = init_container_status(&pod, "init-3")
+                .and_then(|container_status| container_status.state.as_ref())
+                .filter(|state| state.running.is_some() || state.terminated.is_some())
+                .is_some();
+            if init_3_started {
+                result.combine::<(), _>(&Err(
+                    "The init container init-3 was started although its predecessor failed",
+                ));
+            }
+
+            let main_container = marker_service().name;
+            let main_started = container_status(&pod, &main_container)
+                .and_then(|container_status| container_status.state.as_ref())
+                .filter(|state| state.running.is_some() || state.terminated.is_some())
+                .is_some();
+            if main_started {
+                result.combine::<(), _>(&Err(
+                    "The main container was started although an init container failed",
+                ));
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
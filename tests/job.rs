@@ -1,62 +1,80 @@
+mod util;
+
+use anyhow::Result;
 use integration_test_commons::test::prelude::*;
+use rstest::rstest;
+
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::repository::StackableRepositoryInstance;
+use crate::util::result::TestResult;
+use crate::util::services::configurable_exit_service;
+use crate::util::status::{verify_status, TimestampValidator};
 
 struct ExitService<'a> {
-    client: &'a TestKubeClient,
-    pod: TemporaryResource<'a, Pod>,
+    client: &'a KubeClient,
+    repository_result: Result<StackableRepositoryInstance>,
+    pod_result: Result<Pod>,
+    timestamp_validator: TimestampValidator,
 }
 
 impl<'a> ExitService<'a> {
-    pub fn new(client: &'a TestKubeClient, exit_code: i32) -> Self {
-        setup_repository(client);
-
-        let pod = TemporaryResource::new(
+    pub async fn new(client: &'a KubeClient, result: &mut TestResult, exit_code: i32) -> Self {
+        ExitService::start(
             client,
-            &with_unique_name(&formatdoc!(
-                "
-                apiVersion: v1
-                kind: Pod
-                metadata:
-                  name: agent-service-integration-test-job
-                spec:
-                  containers:
-                    - name: exit-service
-                      image: exit-service:1.0.0
-                      command:
-                        - exit-service-1.0.0/start.sh
-                      env:
-                        - name: EXIT_CODE
-                          value: {exit_code}
-                  restartPolicy: Never
-                  nodeSelector:
-                    kubernetes.io/arch: stackable-linux
-                  tolerations:
-                    - key: kubernetes.io/arch
-                      operator: Equal
-                      value: stackable-linux
-                ",
-                exit_code = exit_code
-            )),
-        );
+            result,
+            &[("EXIT_CODE", exit_code.to_string().as_str())],
+        )
+        .await
+    }
 
-        ExitService { client, pod }
+    /// Starts an exit-service which sends the given signal, e.g. `KILL`,
+    /// to itself.
+    pub async fn killed_by(client: &'a KubeClient, result: &mut TestResult, signal: &str) -> Self {
+        ExitService::start(client, result, &[("SIGNAL", signal)]).await
     }
 
-    pub fn verify_terminated(&mut self) {
-        self.client.verify_status::<Pod, _>(&self.pod, |pod| {
+    async fn start(client: &'a KubeClient, result: &mut TestResult, env: &[(&str, &str)]) -> Self {
+        let timestamp_validator = TimestampValidator::new();
+
+        let job = configurable_exit_service();
+        let pod_definition =
+            job.pod_with_env(&unique_name("agent-service-integration-test-job"), env);
+
+        let (repository_result, pod_result) =
+            set_up(client, result, &[&job], &pod_definition).await;
+
+        ExitService {
+            client,
+            repository_result,
+            pod_result,
+            timestamp_validator,
+        }
+    }
+
+    /// Waits until the job is terminated and returns the current state of the pod.
+    pub async fn verify_terminated(&self, result: &mut TestResult) -> Option<Pod> {
+        let pod = self.pod_result.as_ref().ok()?;
+
+        let verify_status_result = verify_status(self.client, pod, |pod| {
             let phase = ExitService::phase_from(pod);
             let container_terminated = ExitService::terminated_container_state_from(pod).is_some();
             (phase == "Succeeded" || phase == "Failed") && container_terminated
-        });
+        })
+        .await;
+        result.combine(&verify_status_result);
 
-        self.pod.update();
-    }
+        let get_status_result = self.client.get_status(pod).await;
+        result.combine(&get_status_result);
 
-    pub fn phase(&self) -> String {
-        ExitService::phase_from(&self.pod)
+        if let Ok(pod) = &get_status_result {
+            result.combine(&self.timestamp_validator.validate(pod));
+        }
+
+        get_status_result.ok()
     }
 
-    pub fn terminated_container_state(&self) -> Option<ContainerStateTerminated> {
-        ExitService::terminated_container_state_from(&self.pod)
+    pub async fn close(self, result: &mut TestResult) {
+        tear_down(self.client, result, self.repository_result, self.pod_result).await;
     }
 
     fn phase_from(pod: &Pod) -> String {
@@ -76,57 +94,140 @@ impl<'a> ExitService<'a> {
     }
 }
 
-#[test]
-fn successful_job_should_have_phase_succeeded_and_error_code_0() {
-    let client = TestKubeClient::new();
+#[tokio::test]
+async fn successful_job_should_have_phase_succeeded_and_error_code_0() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
 
     let exit_code = 0;
-    let mut exit_service = ExitService::new(&client, exit_code);
-
-    exit_service.verify_terminated();
-
-    asserting("phase")
-        .that(&exit_service.phase())
-        .is_equal_to(String::from("Succeeded"));
-
-    let container_state = exit_service
-        .terminated_container_state()
-        .expect("Terminated container state expected");
-    asserting("exit code")
-        .that(&container_state.exit_code)
-        .is_equal_to(0);
-    asserting("message")
-        .that(&container_state.message)
-        .is_equal_to(Some(String::from("Completed")));
-    asserting("reason")
-        .that(&container_state.message)
-        .is_equal_to(Some(String::from("Completed")));
+    let exit_service = ExitService::new(&client, &mut result, exit_code).await;
+
+    if let Some(pod) = exit_service.verify_terminated(&mut result).await {
+        result.check_eq(
+            "phase",
+            &String::from("Succeeded"),
+            &ExitService::phase_from(&pod),
+        );
+
+        match ExitService::terminated_container_state_from(&pod) {
+            Some(container_state) => {
+                result.check_eq("exit code", &0, &container_state.exit_code);
+                result.check_eq(
+                    "message",
+                    &Some(String::from("Completed")),
+                    &container_state.message,
+                );
+                result.check_eq(
+                    "reason",
+                    &Some(String::from("Completed")),
+                    &container_state.reason,
+                );
+            }
+            None => result.combine::<(), _>(&Err("Terminated container state expected")),
+        }
+    }
+
+    exit_service.close(&mut result).await;
+
+    result.into()
 }
 
-#[test]
-fn failed_job_should_have_phase_failed_and_error_code_1() {
-    let client = TestKubeClient::new();
+#[tokio::test]
+async fn failed_job_should_have_phase_failed_and_error_code_1() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
 
     // All non-zero exit codes are mapped by the agent to 1.
     let exit_code = 42;
-    let mut exit_service = ExitService::new(&client, exit_code);
-
-    exit_service.verify_terminated();
-
-    asserting("phase")
-        .that(&exit_service.phase())
-        .is_equal_to(String::from("Failed"));
-
-    let container_state = exit_service
-        .terminated_container_state()
-        .expect("Terminated container state expected");
-    asserting("exit code")
-        .that(&container_state.exit_code)
-        .is_equal_to(1);
-    asserting("message")
-        .that(&container_state.message)
-        .is_equal_to(Some(String::from("Error")));
-    asserting("reason")
-        .that(&container_state.message)
-        .is_equal_to(Some(String::from("Error")));
+    let exit_service = ExitService::new(&client, &mut result, exit_code).await;
+
+    if let Some(pod) = exit_service.verify_terminated(&mut result).await {
+        result.check_eq(
+            "phase",
+            &String::from("Failed"),
+            &ExitService::phase_from(&pod),
+        );
+
+        match ExitService::terminated_container_state_from(&pod) {
+            Some(container_state) => {
+                result.check_eq("exit code", &1, &container_state.exit_code);
+                result.check_eq(
+                    "message",
+                    &Some(String::from("Error")),
+                    &container_state.message,
+                );
+                result.check_eq(
+                    "reason",
+                    &Some(String::from("Error")),
+                    &container_state.reason,
+                );
+            }
+            None => result.combine::<(), _>(&Err("Terminated container state expected")),
+        }
+    }
+
+    exit_service.close(&mut result).await;
+
+    result.into()
+}
+
+/// The agent maps every non-zero exit code and every termination by a
+/// signal to the exit code 1 and does not report signals.
+#[rstest]
+#[case::exit_code_0(Some(0), None, "Succeeded", 0, "Completed")]
+#[case::exit_code_1(Some(1), None, "Failed", 1, "Error")]
+#[case::exit_code_2(Some(2), None, "Failed", 1, "Error")]
+#[case::exit_code_126(Some(126), None, "Failed", 1, "Error")]
+#[case::exit_code_127(Some(127), None, "Failed", 1, "Error")]
+#[case::exit_code_137(Some(137), None, "Failed", 1, "Error")]
+#[case::exit_code_139(Some(139), None, "Failed", 1, "Error")]
+#[case::exit_code_255(Some(255), None, "Failed", 1, "Error")]
+#[case::killed_by_sigkill(None, Some("KILL"), "Failed", 1, "Error")]
+#[case::killed_by_sigsegv(None, Some("SEGV"), "Failed", 1, "Error")]
+#[tokio::test]
+async fn terminated_job_should_report_the_mapped_exit_code(
+    #[case] exit_code: Option<i32>,
+    #[case] signal: Option<&str>,
+    #[case] expected_phase: &str,
+    #[case] expected_exit_code: i32,
+    #[case] expected_reason: &str,
+) -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let exit_service = match (exit_code, signal) {
+        (Some(exit_code), None) => ExitService::new(&client, &mut result, exit_code).await,
+        (None, Some(signal)) => ExitService::killed_by(&client, &mut result, signal).await,
+        other => panic!("invalid parameters: {:?}", other),
+    };
+
+    if let Some(pod) = exit_service.verify_terminated(&mut result).await {
+        result.check_eq(
+            "phase",
+            &String::from(expected_phase),
+            &ExitService::phase_from(&pod),
+        );
+
+        match ExitService::terminated_container_state_from(&pod) {
+            Some(container_state) => {
+                result.check_eq("exit code", &expected_exit_code, &container_state.exit_code);
+                result.check_eq(
+                    "reason",
+                    &Some(String::from(expected_reason)),
+                    &container_state.reason,
+                );
+                result.check_eq(
+                    "message",
+                    &Some(String::from(expected_reason)),
+                    &container_state.message,
+                );
+                result.check_eq("signal", &None, &container_state.signal);
+            }
+            None => result.combine::<(), _>(&Err("Terminated container state expected")),
+        }
+    }
+
+    exit_service.close(&mut result).await;
+
+    result.into()
 }
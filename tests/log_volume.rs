@@ -0,0 +1,312 @@
+mod util;
+
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use rstest::rstest;
+
+use crate::util::features::{feature_enabled, LOGS};
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::logs::raw_logs;
+use crate::util::repository::StackableRepositoryInstance;
+use crate::util::result::TestResult;
+use crate::util::services::{log_flood_line, log_flood_service, INVALID_UTF8_SEQUENCE};
+use crate::util::status::verify_pod_condition;
+
+/// Line printed by the log-flood-service after all other lines
+const END_MARKER: &str = "FLOOD_END";
+
+/// Maximum time to wait for all lines of the log-flood-service
+const FLOOD_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Default value of `LineMax` in journald.conf
+///
+/// Journald splits longer lines into several entries.
+const JOURNALD_LINE_MAX: usize = 48 * 1024;
+
+struct LogFloodService<'a> {
+    client: &'a KubeClient,
+    repository_result: Result<StackableRepositoryInstance>,
+    pod_result: Result<Pod>,
+    pub logs_enabled: bool,
+}
+
+impl<'a> LogFloodService<'a> {
+    /// Starts the log-flood-service with the given environment and waits
+    /// until all lines are logged.
+    pub async fn new(
+        client: &'a KubeClient,
+        result: &mut TestResult,
+        env: &[(&str, &str)],
+    ) -> LogFloodService<'a> {
+        let service = log_flood_service();
+        let pod_definition =
+            service.pod_with_env(&unique_name("agent-log-volume-integration-test"), env);
+
+        let (repository_result, pod_result) =
+            set_up(client, result, &[&service], &pod_definition).await;
+
+        let mut logs_enabled = false;
+
+        if let Ok(pod) = &pod_result {
+            let pod_ready = verify_pod_condition(client, pod, "Ready").await;
+            result.combine(&pod_ready);
+
+            logs_enabled = feature_enabled(client, result, pod, LOGS).await;
+
+            if logs_enabled {
+                let flood_ended = wait_for_end_marker(pod).await;
+                result.combine(&flood_ended);
+            }
+        }
+
+        LogFloodService {
+            client,
+            repository_result,
+            pod_result,
+            logs_enabled,
+        }
+    }
+
+    /// Returns the logs without the end marker.
+    pub async fn get_logs(&self, result: &mut TestResult) -> Vec<String> {
+        if let Ok(pod) = &self.pod_result {
+            let logs_result = self.client.get_logs(pod, &LogParams::default()).await;
+            result.combine(&logs_result);
+            let mut logs = logs_result.unwrap_or_default();
+            if logs.last().map(String::as_str) == Some(END_MARKER) {
+                logs.pop();
+            }
+            logs
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the undecoded logs.
+    pub async fn get_raw_logs(&self, result: &mut TestResult) -> Vec<u8> {
+        if let Ok(pod) = &self.pod_result {
+            let logs_result = raw_logs(pod, &LogParams::default()).await;
+            result.combine(&logs_result);
+            logs_result.unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub async fn close(self, result: &mut TestResult) {
+        tear_down(self.client, result, self.repository_result, self.pod_result).await;
+    }
+}
+
+/// Polls the logs of the given pod until they contain the end marker.
+async fn wait_for_end_marker(pod: &Pod) -> Result<()> {
+    let start = Instant::now();
+
+    loop {
+        let logs = raw_logs(pod, &LogParams::default()).await?;
+        if logs
+            .windows(END_MARKER.len())
+            .any(|window| window == END_MARKER.as_bytes())
+        {
+            return Ok(());
+        }
+        if start.elapsed() > FLOOD_TIMEOUT {
+            return Err(anyhow!(
+                "The log-flood-service did not finish within {:?}; {} bytes were logged",
+                FLOOD_TIMEOUT,
+                logs.len()
+            ));
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
+
+#[rstest]
+#[case::stdout_unlimited_rate("stdout", 0)]
+#[case::stderr_unlimited_rate("stderr", 0)]
+#[case::stdout_limited_rate("stdout", 2000)]
+#[case::stderr_limited_rate("stderr", 2000)]
+#[tokio::test]
+async fn flooded_logs_should_be_complete_and_in_order(
+    #[case] stream: &str,
+    #[case] rate: u32,
+) -> Result<()> {
+    const LINES: usize = 10_000;
+    const LINE_LENGTH: usize = 100;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let flood_service = LogFloodService::new(
+        &client,
+        &mut result,
+        &[
+            ("LINES", LINES.to_string().as_str()),
+            ("LINE_LENGTH", LINE_LENGTH.to_string().as_str()),
+            ("RATE", rate.to_string().as_str()),
+            ("STREAM", stream),
+        ],
+    )
+    .await;
+
+    if flood_service.logs_enabled {
+        let logs = flood_service.get_logs(&mut result).await;
+
+        result.check_eq("number of logged lines", &LINES, &logs.len());
+
+        let first_mismatch = logs
+            .iter()
+            .enumerate()
+            .find(|(index, line)| **line != log_flood_line(index + 1, LINE_LENGTH));
+        if let Some((index, line)) = first_mismatch {
+            result.combine::<(), _>(&Err(format!(
+                "Line {} was expected to be [{}] but was [{}]; lines were lost, reordered, or \
+                corrupted",
+                index + 1,
+                log_flood_line(index + 1, LINE_LENGTH),
+                line
+            )));
+        }
+    }
+
+    flood_service.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn lines_longer_than_the_journald_limit_should_not_lose_content() -> Result<()> {
+    const LINES: usize = 3;
+    const LINE_LENGTH: usize = 2 * JOURNALD_LINE_MAX + 1000;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let flood_service = LogFloodService::new(
+        &client,
+        &mut result,
+        &[
+            ("LINES", LINES.to_string().as_str()),
+            ("LINE_LENGTH", LINE_LENGTH.to_string().as_str()),
+        ],
+    )
+    .await;
+
+    if flood_service.logs_enabled {
+        let logs = flood_service.get_logs(&mut result).await;
+
+        // Journald may split the lines into several entries, so only
+        // the concatenated content is compared.
+        let expected_content = (1..=LINES)
+            .map(|number| log_flood_line(number, LINE_LENGTH))
+            .collect::<String>();
+        let content = logs.concat();
+
+        if content != expected_content {
+            result.combine::<(), _>(&Err(format!(
+                "The content of {} lines with {} bytes each was not preserved; {} of {} \
+                bytes were returned in {} lines",
+                LINES,
+                LINE_LENGTH,
+                content.len(),
+                expected_content.len(),
+                logs.len()
+            )));
+        }
+    }
+
+    flood_service.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn invalid_utf8_should_be_replaced_deterministically() -> Result<()> {
+    const LINES: usize = 10;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let flood_service = LogFloodService::new(
+        &client,
+        &mut result,
+        &[
+            ("LINES", LINES.to_string().as_str()),
+            ("INVALID_UTF8", "true"),
+        ],
+    )
+    .await;
+
+    if flood_service.logs_enabled {
+        let raw_logs = flood_service.get_raw_logs(&mut result).await;
+        let repeated_raw_logs = flood_service.get_raw_logs(&mut result).await;
+
+        if raw_logs != repeated_raw_logs {
+            result.combine::<(), _>(&Err(
+                "Repeated retrievals of the logs returned different content",
+            ));
+        }
+
+        if raw_logs
+            .windows(INVALID_UTF8_SEQUENCE.len())
+            .any(|window| window == INVALID_UTF8_SEQUENCE)
+        {
+            result.combine::<(), _>(&Err(
+                "The invalid UTF-8 sequence was passed through unchanged",
+            ));
+        }
+
+        match String::from_utf8(raw_logs) {
+            Ok(logs) => verify_replacements(&mut result, &logs, LINES),
+            Err(error) => {
+                result.combine::<(), _>(&Err(format!("The logs are not valid UTF-8: {}", error)))
+            }
+        }
+    }
+
+    flood_service.close(&mut result).await;
+
+    result.into()
+}
+
+/// Verifies that every line keeps its valid parts and that the invalid
+/// sequence is replaced by the same non-empty text in every line.
+fn verify_replacements(result: &mut TestResult, logs: &str, lines: usize) {
+    let logs = logs
+        .lines()
+        .filter(|line| *line != END_MARKER)
+        .collect::<Vec<_>>();
+
+    result.check_eq("number of logged lines", &lines, &logs.len());
+
+    let mut replacements = Vec::new();
+
+    for (index, line) in logs.iter().enumerate() {
+        let prefix = format!("{:08} before ", index + 1);
+        let replacement = line
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(" after"));
+
+        match replacement {
+            Some(replacement) if !replacement.is_empty() => replacements.push(replacement),
+            _ => {
+                result.combine::<(), _>(&Err(format!(
+                    "Line {} was expected to match [{}<replacement> after] but was [{}]",
+                    index + 1,
+                    prefix,
+                    line
+                )));
+            }
+        }
+    }
+
+    replacements.dedup();
+    if replacements.len() > 1 {
+        result.combine::<(), _>(&Err(format!(
+            "The invalid UTF-8 sequence was replaced differently: {:?}",
+            replacements
+        )));
+    }
+}
@@ -1,144 +1,758 @@
+mod util;
+
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
 use integration_test_commons::test::prelude::*;
+use k8s_openapi::chrono::{DateTime, Utc};
+
+use crate::util::features::{feature_enabled, LOGS, RESTART_COUNT};
+use crate::util::fixture::{
+    close_repository, create_pod, delete_pod, set_up, start_repository, tear_down, unique_name,
+};
+use crate::util::logs::{raw_logs, LogFollower};
+use crate::util::repository::StackableRepositoryInstance;
+use crate::util::result::TestResult;
+use crate::util::services::{echo_service, flaky_service, ticker_service};
+use crate::util::status::verify_pod_condition;
 
 struct EchoService<'a> {
-    client: &'a TestKubeClient,
-    pod: TemporaryResource<'a, Pod>,
+    client: &'a KubeClient,
+    repository_result: Result<StackableRepositoryInstance>,
+    pod_result: Result<Pod>,
     pub logs_enabled: bool,
 }
 
 impl<'a> EchoService<'a> {
-    pub fn new(client: &'a TestKubeClient, log_output: &[&str]) -> Self {
-        setup_repository(client);
-
+    pub async fn new(client: &'a KubeClient, result: &mut TestResult, log_output: &[&str]) -> Self {
         /// Newline character for LOG_OUTPUT
         ///
-        /// Source code:        \\\\\\\\n
-        /// Pod spec:           \\\\n
+        /// Source code:        \\\\n
+        /// Pod spec:           \\n
         /// Systemd unit file:  \\n
         /// echo-service:       \n
         /// Journal:            separate entries
-        const NEWLINE: &str = "\\\\\\\\n";
+        const NEWLINE: &str = "\\\\n";
 
-        let pod = TemporaryResource::new(
-            client,
-            &with_unique_name(&formatdoc!(
-                r#"
-                apiVersion: v1
-                kind: Pod
-                metadata:
-                  name: agent-logs-integration-test-logs
-                spec:
-                  containers:
-                    - name: echo-service
-                      image: echo-service:1.0.0
-                      command:
-                        - echo-service-1.0.0/start.sh
-                      env:
-                        - name: LOG_OUTPUT
-                          value: "{log_output}"
-                  nodeSelector:
-                    kubernetes.io/arch: stackable-linux
-                  tolerations:
-                    - key: kubernetes.io/arch
-                      operator: Equal
-                      value: stackable-linux
-                "#,
-                log_output = log_output.join(NEWLINE)
-            )),
+        let service = echo_service();
+        let pod_definition = service.pod_with_env(
+            &unique_name("agent-logs-integration-test-logs"),
+            &[("LOG_OUTPUT", log_output.join(NEWLINE).as_str())],
         );
 
-        client.verify_pod_condition(&pod, "Ready");
+        let (repository_result, pod_result) =
+            set_up(client, result, &[&service], &pod_definition).await;
 
-        const ANNOTATION_KEY_FEATURE_LOGS: &str = "featureLogs";
+        let mut logs_enabled = false;
 
-        let logs_enabled = match client
-            .get_annotation::<Pod>(&pod, ANNOTATION_KEY_FEATURE_LOGS)
-            .as_ref()
-        {
-            "true" => true,
-            "false" => false,
-            value => panic!(
-                "Pod annotation [{}] contains unknown value [{}]; \
-                expected [true] or [false]",
-                ANNOTATION_KEY_FEATURE_LOGS, value,
-            ),
-        };
+        if let Ok(pod) = &pod_result {
+            let pod_ready = verify_pod_condition(client, pod, "Ready").await;
+            result.combine(&pod_ready);
+
+            logs_enabled = feature_enabled(client, result, pod, LOGS).await;
+        }
 
         EchoService {
             client,
-            pod,
+            repository_result,
+            pod_result,
             logs_enabled,
         }
     }
 
-    pub fn get_logs(&self, params: &LogParams) -> Vec<String> {
-        self.client.get_logs(&self.pod, params)
+    pub async fn get_logs(&self, result: &mut TestResult, params: &LogParams) -> Vec<String> {
+        if let Ok(pod) = &self.pod_result {
+            let logs_result = self.client.get_logs(pod, params).await;
+            result.combine(&logs_result);
+            logs_result.unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub async fn close(self, result: &mut TestResult) {
+        tear_down(self.client, result, self.repository_result, self.pod_result).await;
     }
 }
 
-#[test]
-fn all_logs_should_be_retrievable() {
-    let client = TestKubeClient::new();
+#[tokio::test]
+async fn all_logs_should_be_retrievable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
 
     let log_output = vec!["line 1", "line 2", "line 3"];
-    let echo_service = EchoService::new(&client, &log_output);
+    let echo_service = EchoService::new(&client, &mut result, &log_output).await;
 
-    let logs = echo_service.get_logs(&LogParams::default());
+    let logs = echo_service
+        .get_logs(&mut result, &LogParams::default())
+        .await;
 
     if echo_service.logs_enabled {
-        assert_equals(&["line 1", "line 2", "line 3"], &logs);
+        check_logs(&mut result, &["line 1", "line 2", "line 3"], &logs);
     } else {
-        assert_that(&logs).is_empty();
+        check_logs(&mut result, &[], &logs);
     }
+
+    echo_service.close(&mut result).await;
+
+    result.into()
 }
 
-#[test]
-fn the_tail_of_logs_should_be_retrievable() {
-    let client = TestKubeClient::new();
+#[tokio::test]
+async fn the_tail_of_logs_should_be_retrievable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
 
     let log_output = vec!["line 1", "line 2", "line 3"];
-    let echo_service = EchoService::new(&client, &log_output);
+    let echo_service = EchoService::new(&client, &mut result, &log_output).await;
 
     let with_tail_lines = |tail_lines| LogParams {
         tail_lines: Some(tail_lines),
         ..Default::default()
     };
 
-    let logs = echo_service.get_logs(&with_tail_lines(0));
-    assert_that(&logs).is_empty();
+    let logs = echo_service
+        .get_logs(&mut result, &with_tail_lines(0))
+        .await;
+    check_logs(&mut result, &[], &logs);
 
     if echo_service.logs_enabled {
-        let logs = echo_service.get_logs(&with_tail_lines(1));
-        assert_equals(&["line 3"], &logs);
+        let logs = echo_service
+            .get_logs(&mut result, &with_tail_lines(1))
+            .await;
+        check_logs(&mut result, &["line 3"], &logs);
 
-        let logs = echo_service.get_logs(&with_tail_lines(2));
-        assert_equals(&["line 2", "line 3"], &logs);
+        let logs = echo_service
+            .get_logs(&mut result, &with_tail_lines(2))
+            .await;
+        check_logs(&mut result, &["line 2", "line 3"], &logs);
 
-        let logs = echo_service.get_logs(&with_tail_lines(3));
-        assert_equals(&["line 1", "line 2", "line 3"], &logs);
+        let logs = echo_service
+            .get_logs(&mut result, &with_tail_lines(3))
+            .await;
+        check_logs(&mut result, &["line 1", "line 2", "line 3"], &logs);
 
-        let logs = echo_service.get_logs(&with_tail_lines(4));
-        assert_equals(&["line 1", "line 2", "line 3"], &logs);
+        let logs = echo_service
+            .get_logs(&mut result, &with_tail_lines(4))
+            .await;
+        check_logs(&mut result, &["line 1", "line 2", "line 3"], &logs);
     }
+
+    echo_service.close(&mut result).await;
+
+    result.into()
 }
 
-#[test]
-fn non_ascii_characters_should_be_handled_correctly_in_the_logs() {
-    let client = TestKubeClient::new();
+#[tokio::test]
+async fn non_ascii_characters_should_be_handled_correctly_in_the_logs() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
 
     let log_output = vec!["Spade: ♠", "Heart: ♥", "Diamond: ♦", "Club: ♣"];
-    let echo_service = EchoService::new(&client, &log_output);
+    let echo_service = EchoService::new(&client, &mut result, &log_output).await;
 
-    let logs = echo_service.get_logs(&LogParams::default());
+    let logs = echo_service
+        .get_logs(&mut result, &LogParams::default())
+        .await;
 
     if echo_service.logs_enabled {
-        assert_equals(&["Spade: ♠", "Heart: ♥", "Diamond: ♦", "Club: ♣"], &logs);
+        check_logs(
+            &mut result,
+            &["Spade: ♠", "Heart: ♥", "Diamond: ♦", "Club: ♣"],
+            &logs,
+        );
     } else {
-        assert_that(&logs).is_empty();
+        check_logs(&mut result, &[], &logs);
+    }
+
+    echo_service.close(&mut result).await;
+
+    result.into()
+}
+
+/// Number of lines printed by the time-spaced ticker-service
+const SPACED_TICKS: usize = 5;
+
+/// Interval between the lines of the time-spaced ticker-service
+const SPACED_TICK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Tolerance for the timestamps of the time-spaced ticks
+const TIMESTAMP_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// Ticker-service which printed all of its lines with a gap of
+/// [`SPACED_TICK_INTERVAL`]
+struct TimeSpacedTicker<'a> {
+    client: &'a KubeClient,
+    repository_result: Result<StackableRepositoryInstance>,
+    pod_result: Result<Pod>,
+    pub logs_enabled: bool,
+}
+
+impl<'a> TimeSpacedTicker<'a> {
+    /// Starts the ticker-service and waits until all lines are printed.
+    pub async fn new(client: &'a KubeClient, result: &mut TestResult) -> TimeSpacedTicker<'a> {
+        let service = ticker_service();
+        let pod_definition = service.pod_with_env(
+            &unique_name("agent-logs-integration-test-time-spaced"),
+            &[
+                (
+                    "INTERVAL",
+                    SPACED_TICK_INTERVAL.as_secs().to_string().as_str(),
+                ),
+                ("COUNT", SPACED_TICKS.to_string().as_str()),
+            ],
+        );
+
+        let (repository_result, pod_result) =
+            set_up(client, result, &[&service], &pod_definition).await;
+
+        let mut logs_enabled = false;
+
+        if let Ok(pod) = &pod_result {
+            let pod_ready = verify_pod_condition(client, pod, "Ready").await;
+            result.combine(&pod_ready);
+
+            logs_enabled = feature_enabled(client, result, pod, LOGS).await;
+
+            if logs_enabled {
+                let all_ticks_printed = wait_for_log_lines(client, pod, SPACED_TICKS).await;
+                result.combine(&all_ticks_printed);
+            }
+        }
+
+        TimeSpacedTicker {
+            client,
+            repository_result,
+            pod_result,
+            logs_enabled,
+        }
+    }
+
+    pub async fn get_logs(&self, result: &mut TestResult, params: &LogParams) -> Vec<String> {
+        if let Ok(pod) = &self.pod_result {
+            let logs_result = self.client.get_logs(pod, params).await;
+            result.combine(&logs_result);
+            logs_result.unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the logs with the timestamps parsed.
+    pub async fn get_timestamped_logs(
+        &self,
+        result: &mut TestResult,
+    ) -> Vec<(DateTime<Utc>, String)> {
+        let params = LogParams {
+            timestamps: true,
+            ..Default::default()
+        };
+        let logs = self.get_logs(result, &params).await;
+
+        let parse_result = logs
+            .iter()
+            .map(|line| parse_timestamped_line(line))
+            .collect::<Result<Vec<_>>>();
+        result.combine(&parse_result);
+        parse_result.unwrap_or_default()
+    }
+
+    pub async fn close(self, result: &mut TestResult) {
+        tear_down(self.client, result, self.repository_result, self.pod_result).await;
+    }
+}
+
+/// Polls the logs of the given pod until they contain the given number
+/// of lines.
+async fn wait_for_log_lines(client: &KubeClient, pod: &Pod, lines: usize) -> Result<()> {
+    let timeout = SPACED_TICK_INTERVAL * lines as u32 + Duration::from_secs(30);
+    let start = Instant::now();
+
+    loop {
+        let logs = client.get_logs(pod, &LogParams::default()).await?;
+        if logs.len() >= lines {
+            return Ok(());
+        }
+        if start.elapsed() > timeout {
+            return Err(anyhow!(
+                "Only {} of {} log lines were printed within {:?}",
+                logs.len(),
+                lines,
+                timeout
+            ));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Splits a line retrieved with `timestamps` into the RFC3339 timestamp
+/// and the content.
+fn parse_timestamped_line(line: &str) -> Result<(DateTime<Utc>, String)> {
+    let (timestamp, content) = line
+        .split_once(' ')
+        .ok_or_else(|| anyhow!("The line [{}] does not start with a timestamp", line))?;
+    let timestamp = DateTime::parse_from_rfc3339(timestamp).map_err(|error| {
+        anyhow!(
+            "The timestamp of the line [{}] is not in the RFC3339 format: {}",
+            line,
+            error
+        )
+    })?;
+    Ok((timestamp.with_timezone(&Utc), String::from(content)))
+}
+
+fn tick_lines(ticks: impl Iterator<Item = usize>) -> Vec<String> {
+    ticks.map(|tick| format!("tick {}", tick)).collect()
+}
+
+#[tokio::test]
+async fn logs_since_seconds_should_be_retrievable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let ticker = TimeSpacedTicker::new(&client, &mut result).await;
+
+    if ticker.logs_enabled {
+        let with_since_seconds = |since_seconds| LogParams {
+            since_seconds: Some(since_seconds),
+            ..Default::default()
+        };
+
+        let logs = ticker
+            .get_logs(&mut result, &with_since_seconds(3600))
+            .await;
+        result.check_eq(
+            "logs of the last hour",
+            &tick_lines(1..=SPACED_TICKS),
+            &logs,
+        );
+
+        // The last tick was printed shortly before, the second last
+        // three seconds earlier, and the first ticks long before.
+        let logs = ticker.get_logs(&mut result, &with_since_seconds(5)).await;
+        let all_ticks = tick_lines(1..=SPACED_TICKS);
+        if !all_ticks.ends_with(&logs) || logs.is_empty() || logs.len() > 2 {
+            result.combine::<(), _>(&Err(format!(
+                "The logs of the last 5 seconds should contain the last or the last two \
+                ticks but were {:?}",
+                logs
+            )));
+        }
+    }
+
+    ticker.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_since_a_point_in_time_should_be_retrievable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let ticker = TimeSpacedTicker::new(&client, &mut result).await;
+
+    if ticker.logs_enabled {
+        let timestamped_logs = ticker.get_timestamped_logs(&mut result).await;
+
+        let with_since_time = |since_time| LogParams {
+            since_time: Some(since_time),
+            ..Default::default()
+        };
+
+        if let Some((third_tick_timestamp, _)) = timestamped_logs.get(2) {
+            let logs = ticker
+                .get_logs(&mut result, &with_since_time(*third_tick_timestamp))
+                .await;
+            result.check_eq(
+                "logs since the third tick",
+                &tick_lines(3..=SPACED_TICKS),
+                &logs,
+            );
+        }
+
+        if let Some((last_tick_timestamp, _)) = timestamped_logs.last() {
+            let since_time = *last_tick_timestamp + k8s_openapi::chrono::Duration::seconds(60);
+            let logs = ticker
+                .get_logs(&mut result, &with_since_time(since_time))
+                .await;
+            check_logs(&mut result, &[], &logs);
+        }
+    }
+
+    ticker.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_with_timestamps_should_be_retrievable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let ticker = TimeSpacedTicker::new(&client, &mut result).await;
+
+    if ticker.logs_enabled {
+        let timestamped_logs = ticker.get_timestamped_logs(&mut result).await;
+
+        result.check_eq(
+            "logs without timestamps",
+            &tick_lines(1..=SPACED_TICKS),
+            &timestamped_logs
+                .iter()
+                .map(|(_, content)| content.to_owned())
+                .collect::<Vec<_>>(),
+        );
+
+        for (previous, next) in timestamped_logs.iter().zip(timestamped_logs.iter().skip(1)) {
+            let gap = (next.0 - previous.0).to_std().unwrap_or_default();
+            if gap + TIMESTAMP_TOLERANCE < SPACED_TICK_INTERVAL
+                || gap > SPACED_TICK_INTERVAL + TIMESTAMP_TOLERANCE
+            {
+                result.combine::<(), _>(&Err(format!(
+                    "The lines [{}] and [{}] were printed {:?} apart but their timestamps \
+                    differ by {:?}",
+                    previous.1, next.1, SPACED_TICK_INTERVAL, gap
+                )));
+            }
+        }
+    }
+
+    ticker.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_should_be_truncated_to_the_byte_limit() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    // Multi-byte characters ensure that the limits also fall within
+    // characters. The agent must omit such a character, therefore the
+    // longest prefix which ends on a character boundary within the
+    // limit is expected.
+    let log_output = vec!["♠♥", "♦♣"];
+    let echo_service = EchoService::new(&client, &mut result, &log_output).await;
+
+    if echo_service.logs_enabled {
+        if let Ok(pod) = &echo_service.pod_result {
+            let complete_logs = format!("{}\n", log_output.join("\n"));
+
+            for limit_bytes in 1..=complete_logs.len() + 1 {
+                let params = LogParams {
+                    limit_bytes: Some(limit_bytes as i64),
+                    ..Default::default()
+                };
+                let logs_result = raw_logs(pod, &params).await;
+                result.combine(&logs_result);
+
+                if let Ok(logs) = logs_result {
+                    let description = format!("The logs with a limit of {} bytes", limit_bytes);
+                    let expected_len = (0..=limit_bytes.min(complete_logs.len()))
+                        .rev()
+                        .find(|&index| complete_logs.is_char_boundary(index))
+                        .unwrap_or_default();
+                    let expected_logs = &complete_logs[..expected_len];
+
+                    match String::from_utf8(logs) {
+                        Ok(logs) if logs.len() > limit_bytes => result.combine::<(), _>(&Err(
+                            format!("{} exceed the limit: {:?}", description, logs),
+                        )),
+                        Ok(logs) if !complete_logs.starts_with(&logs) => {
+                            result.combine::<(), _>(&Err(format!(
+                                "{} are not a prefix of the complete logs {:?}: {:?}",
+                                description, complete_logs, logs
+                            )))
+                        }
+                        Ok(logs) if logs != expected_logs => {
+                            result.combine::<(), _>(&Err(format!(
+                                "{} do not contain the longest prefix {:?} within the limit: {:?}",
+                                description, expected_logs, logs
+                            )))
+                        }
+                        Ok(_) => {}
+                        Err(error) => result.combine::<(), _>(&Err(format!(
+                            "{} are not valid UTF-8: {:?}",
+                            description,
+                            error.as_bytes()
+                        ))),
+                    }
+                }
+            }
+        }
+    }
+
+    echo_service.close(&mut result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_of_the_previous_run_should_be_retrievable_after_a_restart() -> Result<()> {
+    const FAILING_STARTS: usize = 2;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let counter_file = format!("/tmp/{}.counter", unique_name("flaky-service"));
+
+    let service = flaky_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-logs-integration-test-previous"),
+        &[
+            ("COUNTER_FILE", counter_file.as_str()),
+            ("FAILING_STARTS", FAILING_STARTS.to_string().as_str()),
+        ],
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await
+            && feature_enabled(&client, &mut result, pod, LOGS).await
+        {
+            let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+            result.combine(&pod_ready);
+
+            let logs_result = client.get_logs(pod, &LogParams::default()).await;
+            result.combine(&logs_result);
+            if let Ok(logs) = logs_result {
+                check_logs(
+                    &mut result,
+                    &[format!("run {}", FAILING_STARTS + 1).as_str()],
+                    &logs,
+                );
+            }
+
+            let previous_params = LogParams {
+                previous: true,
+                ..Default::default()
+            };
+            let previous_logs_result = client.get_logs(pod, &previous_params).await;
+            result.combine(&previous_logs_result);
+            if let Ok(previous_logs) = previous_logs_result {
+                check_logs(
+                    &mut result,
+                    &[format!("run {}", FAILING_STARTS).as_str()],
+                    &previous_logs,
+                );
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_of_the_previous_run_should_not_be_retrievable_without_a_restart() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let log_output = vec!["line 1"];
+    let echo_service = EchoService::new(&client, &mut result, &log_output).await;
+
+    if echo_service.logs_enabled {
+        if let Ok(pod) = &echo_service.pod_result {
+            let params = LogParams {
+                previous: true,
+                ..Default::default()
+            };
+            match raw_logs(pod, &params).await {
+                Ok(logs) => result.combine::<(), _>(&Err(format!(
+                    "Logs of a previous run were returned although the service was not \
+                    restarted: {:?}",
+                    String::from_utf8_lossy(&logs)
+                ))),
+                Err(error) if is_previous_container_not_found(&error) => {}
+                Err(error) => result.combine::<(), _>(&Err(format!(
+                    "The request for the logs of a previous run was expected to be \
+                    rejected with [400 Bad Request] but failed with: {:?}",
+                    error
+                ))),
+            }
+        }
+    }
+
+    echo_service.close(&mut result).await;
+
+    result.into()
+}
+
+/// Returns true if the given error states that there is no previous
+/// container, i.e. if the API server rejected the request with the
+/// status `400 Bad Request` or with a message like `previous terminated
+/// container "..." in pod "..." not found`.
+fn is_previous_container_not_found(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<kube::Error>() {
+        Some(kube::Error::Api(response)) => {
+            response.code == 400
+                || (response.message.contains("previous terminated container")
+                    && response.message.contains("not found"))
+        }
+        _ => false,
+    }
+}
+
+/// Interval between the lines of the ticker-service in the follow tests
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum delay between printing a line and receiving it in the
+/// followed logs
+const MAX_LOG_LATENCY: Duration = Duration::from_secs(3);
+
+#[tokio::test]
+async fn followed_logs_should_arrive_incrementally_and_in_order() -> Result<()> {
+    const LINES: usize = 10;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let service = ticker_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-logs-integration-test-follow"),
+        &[("INTERVAL", TICK_INTERVAL.as_secs().to_string().as_str())],
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        if feature_enabled(&client, &mut result, pod, LOGS).await {
+            // Only lines printed after the start of the stream are
+            // received, so their arrival times can be compared.
+            let params = LogParams {
+                tail_lines: Some(0),
+                ..Default::default()
+            };
+            let follower_result = LogFollower::start(pod, &params).await;
+            result.combine(&follower_result);
+
+            if let Ok(mut follower) = follower_result {
+                let mut arrivals = Vec::new();
+
+                for _ in 0..LINES {
+                    let line_result = follower.next_line(TICK_INTERVAL + MAX_LOG_LATENCY).await;
+                    result.combine(&line_result);
+
+                    match line_result {
+                        Ok(Some(line)) => arrivals.push((line, Instant::now())),
+                        Ok(None) => {
+                            result.combine::<(), _>(&Err("The log stream ended unexpectedly"));
+                            break;
+                        }
+                        Err(_) => break,
+                    }
+                }
+
+                verify_ticks(&mut result, &arrivals);
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn followed_logs_should_end_when_the_pod_is_deleted() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let service = ticker_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-logs-integration-test-follow-deletion"),
+        &[("INTERVAL", TICK_INTERVAL.as_secs().to_string().as_str())],
+    );
+
+    let repository_result = start_repository(&client, &mut result, &[&service]).await;
+    let pod_result = create_pod(&client, &mut result, &pod_definition).await;
+
+    let mut follower = None;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        if feature_enabled(&client, &mut result, pod, LOGS).await {
+            let follower_result = LogFollower::start(pod, &LogParams::default()).await;
+            result.combine(&follower_result);
+
+            if let Ok(mut started_follower) = follower_result {
+                let line_result = started_follower
+                    .next_line(TICK_INTERVAL + MAX_LOG_LATENCY)
+                    .await;
+                result.combine(&line_result);
+                follower = Some(started_follower);
+            }
+        }
+    }
+
+    delete_pod(&client, &mut result, pod_result).await;
+
+    if let Some(mut follower) = follower {
+        let end_result = follower.read_to_end(client.timeouts.delete).await;
+        result.combine(&end_result);
+    }
+
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
+}
+
+/// Verifies that the given lines of the ticker-service are consecutive
+/// and arrived one by one.
+fn verify_ticks(result: &mut TestResult, arrivals: &[(String, Instant)]) {
+    let ticks = arrivals
+        .iter()
+        .map(|(line, _)| {
+            line.strip_prefix("tick ")
+                .and_then(|tick| tick.parse::<u32>().ok())
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(Some(first_tick)) = ticks.first() {
+        let expected_ticks = (0..ticks.len() as u32)
+            .map(|offset| Some(first_tick + offset))
+            .collect::<Vec<_>>();
+        result.check_eq("followed ticks", &expected_ticks, &ticks);
+    } else if !arrivals.is_empty() {
+        result.combine::<(), _>(&Err(format!(
+            "Unexpected line in the followed logs: {:?}",
+            arrivals[0].0
+        )));
+    }
+
+    // If the lines were delivered in batches instead of incrementally
+    // then the time between the first and the last arrival would be
+    // shorter than the time between printing them.
+    if let (Some((_, first_arrival)), Some((_, last_arrival))) = (arrivals.first(), arrivals.last())
+    {
+        let expected_duration = TICK_INTERVAL * (arrivals.len() as u32 - 1);
+        let duration = *last_arrival - *first_arrival;
+        if duration + MAX_LOG_LATENCY < expected_duration {
+            result.combine::<(), _>(&Err(format!(
+                "{} lines which were printed within {:?} arrived within {:?}, so they were \
+                not followed incrementally",
+                arrivals.len(),
+                expected_duration,
+                duration
+            )));
+        }
     }
 }
 
-fn assert_equals(expected: &[&str], actual: &[String]) {
-    assert_that(&actual.iter().map(String::as_ref).collect::<Vec<_>>())
-        .equals_iterator(&expected.iter());
+fn check_logs(result: &mut TestResult, expected: &[&str], actual: &[String]) {
+    result.check_eq(
+        "logs",
+        expected,
+        &actual.iter().map(String::as_ref).collect::<Vec<_>>()[..],
+    );
 }
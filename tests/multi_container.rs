@@ -0,0 +1,194 @@
+mod util;
+
+use anyhow::Result;
+use integration_test_commons::test::prelude::*;
+
+use crate::util::features::{feature_enabled, LOGS};
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::result::TestResult;
+use crate::util::services::{configurable_exit_service, echo_service, noop_service};
+use crate::util::status::{
+    container_status, is_running, phase, terminated_state, verify_pod_condition, verify_status,
+};
+use crate::util::test_package::{multi_container_pod, set_env};
+
+#[tokio::test]
+async fn containers_should_have_independent_statuses() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let echo_service = echo_service();
+    let noop_service = noop_service();
+    let packages = [&echo_service, &noop_service];
+
+    let pod_definition = multi_container_pod(
+        &unique_name("agent-multi-container-integration-test-statuses"),
+        &packages,
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &packages, &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            let container_statuses = pod
+                .status
+                .as_ref()
+                .and_then(|status| status.container_statuses.as_ref());
+            result.check_eq(
+                "number of container statuses",
+                &Some(packages.len()),
+                &container_statuses.map(Vec::len),
+            );
+
+            for package in packages.iter() {
+                match container_status(&pod, &package.name) {
+                    Some(container_status) => {
+                        result.check_eq(
+                            &format!("readiness of container {}", package.name),
+                            &true,
+                            &container_status.ready,
+                        );
+                        result.check_eq(
+                            &format!("image of container {}", package.name),
+                            &format!("{}:{}", package.name, package.version),
+                            &container_status.image,
+                        );
+                        result.check_eq(
+                            &format!("running state of container {}", package.name),
+                            &true,
+                            &is_running(&pod, &package.name),
+                        );
+                    }
+                    None => result.combine::<(), _>(&Err(format!(
+                        "The status of container {} is missing",
+                        package.name
+                    ))),
+                }
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn logs_should_be_retrievable_per_container() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let echo_service = echo_service();
+    let noop_service = noop_service();
+    let packages = [&echo_service, &noop_service];
+
+    let mut pod_definition = multi_container_pod(
+        &unique_name("agent-multi-container-integration-test-logs"),
+        &packages,
+    );
+    set_env(&mut pod_definition, &[("LOG_OUTPUT", "echo-service line")]);
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &packages, &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        if feature_enabled(&client, &mut result, pod, LOGS).await {
+            let expected_logs = [
+                (&echo_service, "echo-service line"),
+                (&noop_service, "test-service started"),
+            ];
+
+            for (package, expected_line) in expected_logs.iter() {
+                let params = LogParams {
+                    container: Some(package.name.to_owned()),
+                    ..Default::default()
+                };
+                let logs_result = client.get_logs(pod, &params).await;
+                result.combine(&logs_result);
+
+                if let Ok(logs) = logs_result {
+                    result.check_eq(
+                        &format!("logs of container {}", package.name),
+                        &[*expected_line][..],
+                        &logs.iter().map(String::as_str).collect::<Vec<_>>()[..],
+                    );
+                }
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn pod_should_keep_running_when_one_container_fails() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let echo_service = echo_service();
+    let exit_service = configurable_exit_service();
+    let packages = [&echo_service, &exit_service];
+
+    let mut pod_definition = multi_container_pod(
+        &unique_name("agent-multi-container-integration-test-failure"),
+        &packages,
+    );
+    set_env(&mut pod_definition, &[("EXIT_CODE", "1")]);
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        spec.restart_policy = Some(String::from("Never"));
+    }
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &packages, &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let failing_container = exit_service.name.to_owned();
+        let running_container = echo_service.name.to_owned();
+        let verify_status_result = verify_status(&client, pod, move |pod| {
+            terminated_state(pod, &failing_container).is_some()
+                && is_running(pod, &running_container)
+        })
+        .await;
+        result.combine(&verify_status_result);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            result.check_eq("pod phase", "Running", phase(&pod).as_str());
+
+            result.check_eq(
+                "exit code of the failed container",
+                &Some(1),
+                &terminated_state(&pod, &exit_service.name).map(|terminated| terminated.exit_code),
+            );
+            result.check_eq(
+                "running state of the remaining container",
+                &true,
+                &is_running(&pod, &echo_service.name),
+            );
+            result.check_eq(
+                "readiness of the failed container",
+                &Some(false),
+                &container_status(&pod, &exit_service.name)
+                    .map(|container_status| container_status.ready),
+            );
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
@@ -0,0 +1,162 @@
+mod util;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use serde_json::Value;
+
+use crate::util::fixture::{close_repository, delete_pod, set_up, tear_down, unique_name};
+use crate::util::http::{format_address, get, get_eventually, random_port};
+use crate::util::result::TestResult;
+use crate::util::services::http_echo_service;
+use crate::util::status::verify_pod_condition;
+
+/// Period in which a ready service must become reachable
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tokio::test]
+async fn service_should_be_reachable_on_the_reported_ip_addresses() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let port = random_port();
+    let pod_name = unique_name("agent-network-integration-test-reachable");
+
+    let service = http_echo_service();
+    let pod_definition = service.pod_with_env(
+        &pod_name,
+        &[
+            ("POD_NAME", pod_name.as_str()),
+            ("PORT", port.to_string().as_str()),
+        ],
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            let status = pod.status.unwrap_or_default();
+
+            for (description, ip) in &[("pod IP", &status.pod_ip), ("host IP", &status.host_ip)] {
+                let identity_result =
+                    verify_identity(description, ip.as_deref(), port, &pod_name).await;
+                result.combine(&identity_result);
+            }
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn service_should_not_be_reachable_after_the_pod_was_deleted() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let port = random_port();
+    let pod_name = unique_name("agent-network-integration-test-unreachable");
+
+    let service = http_echo_service();
+    let pod_definition = service.pod_with_env(
+        &pod_name,
+        &[
+            ("POD_NAME", pod_name.as_str()),
+            ("PORT", port.to_string().as_str()),
+        ],
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    let mut pod_ip = None;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        pod_ip = get_status_result
+            .ok()
+            .and_then(|pod| pod.status)
+            .and_then(|status| status.pod_ip);
+
+        let identity_result = verify_identity("pod IP", pod_ip.as_deref(), port, &pod_name).await;
+        result.combine(&identity_result);
+    }
+
+    delete_pod(&client, &mut result, pod_result).await;
+
+    if let Some(address) = pod_ip.and_then(|pod_ip| format_address(&pod_ip, port).ok()) {
+        let uri = format!("http://{}/", address);
+        if get(&uri).await.is_ok() {
+            result.combine::<(), _>(&Err(format!(
+                "Service is still reachable on [{}] after the pod was deleted",
+                uri
+            )));
+        }
+    }
+
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
+}
+
+/// Verifies that the http-echo-service with the given pod name answers
+/// on the given IP address and port.
+async fn verify_identity(
+    description: &str,
+    ip: Option<&str>,
+    port: u16,
+    pod_name: &str,
+) -> Result<()> {
+    let ip = ip.ok_or_else(|| anyhow!("The {} is not set", description))?;
+    let uri = format!("http://{}/identity", format_address(ip, port)?);
+
+    let response = get_eventually(&uri, CONNECTION_TIMEOUT).await?;
+    if response.status != 200 {
+        return Err(anyhow!(
+            "[{}] on the {} responded with status [{}]",
+            uri,
+            description,
+            response.status
+        ));
+    }
+
+    let identity = serde_json::from_str::<Value>(&response.body)?;
+
+    let reported_pod_name = identity["podName"].as_str();
+    if reported_pod_name != Some(pod_name) {
+        return Err(anyhow!(
+            "[{}] on the {} is served by pod [{:?}] but pod [{}] was expected",
+            uri,
+            description,
+            reported_pod_name,
+            pod_name
+        ));
+    }
+
+    let reported_port = identity["environment"]["PORT"].as_str();
+    if reported_port != Some(port.to_string().as_str()) {
+        return Err(anyhow!(
+            "[{}] on the {} reports port [{:?}] but port [{}] was expected",
+            uri,
+            description,
+            reported_port,
+            port
+        ));
+    }
+
+    Ok(())
+}
@@ -1,11 +1,25 @@
+mod util;
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use anyhow::{anyhow, Result};
 use integration_test_commons::test::prelude::*;
 
-#[test]
-fn at_least_one_node_should_be_available() {
-    let client = TestKubeClient::new();
+use crate::util::agent::{AgentVersion, LABEL_AGENT_VERSION};
+use crate::util::nodes::{stackable_nodes, STACKABLE_NODE_SELECTOR};
+use crate::util::result::TestResult;
+
+/// Conditions which every node must report
+const REQUIRED_CONDITIONS: [&str; 4] = ["Ready", "MemoryPressure", "DiskPressure", "PIDPressure"];
+
+#[tokio::test]
+async fn at_least_one_node_should_be_available() -> Result<()> {
+    let client = KubeClient::new().await?;
 
     let mut nodes = client
-        .list_labeled::<Node>("kubernetes.io/arch=stackable-linux")
+        .list_labeled::<Node>(STACKABLE_NODE_SELECTOR)
+        .await?
         .items;
 
     let contains_only_stackable_taints = |node: &Node| {
@@ -24,12 +38,14 @@ fn at_least_one_node_should_be_available() {
     nodes.retain(is_ready);
 
     assert_that(&nodes).is_not_empty();
+
+    Ok(())
 }
 
-#[test]
-fn nodes_should_be_tainted() {
-    let client = TestKubeClient::new();
-    let nodes = client.list_labeled::<Node>("kubernetes.io/arch=stackable-linux");
+#[tokio::test]
+async fn nodes_should_be_tainted() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let nodes = client.list_labeled::<Node>(STACKABLE_NODE_SELECTOR).await?;
 
     for node in nodes {
         let taints = get_node_taints(&node);
@@ -47,4 +63,377 @@ fn nodes_should_be_tainted() {
             })),
         ]);
     }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn agent_version_should_be_detectable() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let agent_version = AgentVersion::detect(&client).await?;
+
+    let mut node_versions = Vec::new();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.as_deref().unwrap_or_default();
+
+        let kubelet_version_result = node
+            .status
+            .as_ref()
+            .and_then(|status| status.node_info.as_ref())
+            .ok_or_else(|| anyhow!("Node [{}] provides no node info", node_name))
+            .and_then(|node_info| node_info.kubelet_version.parse::<AgentVersion>());
+        result.combine(&kubelet_version_result);
+
+        let node_version_result = AgentVersion::from_node(&node);
+        result.combine(&node_version_result);
+
+        let version_label = node
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(LABEL_AGENT_VERSION));
+
+        if let (Ok(kubelet_version), Ok(node_version)) =
+            (kubelet_version_result, node_version_result)
+        {
+            // The label takes precedence over the kubelet version.
+            if version_label.is_none() {
+                result.check_eq(
+                    &format!("Agent version of node [{}]", node_name),
+                    &kubelet_version,
+                    &node_version,
+                );
+            }
+            node_versions.push(node_version);
+        }
+    }
+
+    result.check_eq(
+        "Detected agent version",
+        &node_versions.into_iter().min(),
+        &Some(agent_version),
+    );
+
+    result.into()
+}
+
+#[tokio::test]
+async fn test_case_should_be_skipped_if_the_agent_version_is_not_satisfied() -> Result<()> {
+    let client = KubeClient::new().await?;
+
+    // No version is lower than 0.0.0-0.
+    require_agent_version!(&client, "<0.0.0-0");
+
+    Err(anyhow!(
+        "The test case was not skipped although no agent version satisfies [<0.0.0-0]"
+    ))
+}
+
+#[tokio::test]
+async fn nodes_should_report_capacity_and_allocatable_resources() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.as_deref().unwrap_or_default();
+        let status = node.status.unwrap_or_default();
+
+        let capacity = status.capacity.unwrap_or_default();
+        let allocatable = status.allocatable.unwrap_or_default();
+
+        for resource in &["cpu", "memory", "pods"] {
+            let capacity_value = capacity
+                .get(*resource)
+                .map(|quantity| parse_quantity(&quantity.0));
+            let allocatable_value = allocatable
+                .get(*resource)
+                .map(|quantity| parse_quantity(&quantity.0));
+
+            match (capacity_value, allocatable_value) {
+                (Some(Ok(capacity_value)), Some(Ok(allocatable_value))) => {
+                    if capacity_value <= 0.0 {
+                        result.combine::<(), _>(&Err(format!(
+                            "The {} capacity of node {} is not positive: {}",
+                            resource, node_name, capacity_value
+                        )));
+                    }
+                    if allocatable_value > capacity_value {
+                        result.combine::<(), _>(&Err(format!(
+                            "The allocatable {} of node {} exceeds its capacity: {} > {}",
+                            resource, node_name, allocatable_value, capacity_value
+                        )));
+                    }
+                }
+                (capacity_value, allocatable_value) => {
+                    result.combine::<(), _>(&Err(format!(
+                        "The {} capacity or allocatable of node {} is missing or invalid: \
+                        capacity {:?}, allocatable {:?}",
+                        resource, node_name, capacity_value, allocatable_value
+                    )));
+                }
+            }
+        }
+
+        let pods = allocatable
+            .get("pods")
+            .map(|quantity| quantity.0.parse::<u32>());
+        if !matches!(pods, Some(Ok(_))) {
+            result.combine::<(), _>(&Err(format!(
+                "The allocatable number of pods of node {} is not an integer: {:?}",
+                node_name, pods
+            )));
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn nodes_should_report_node_info() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.to_owned().unwrap_or_default();
+
+        match node.status.and_then(|status| status.node_info) {
+            Some(node_info) => {
+                let required_fields = [
+                    ("architecture", &node_info.architecture),
+                    ("bootID", &node_info.boot_id),
+                    ("kernelVersion", &node_info.kernel_version),
+                    ("kubeletVersion", &node_info.kubelet_version),
+                    ("machineID", &node_info.machine_id),
+                    ("operatingSystem", &node_info.operating_system),
+                    ("osImage", &node_info.os_image),
+                ];
+                for (field, value) in required_fields.iter() {
+                    if value.trim().is_empty() {
+                        result.combine::<(), _>(&Err(format!(
+                            "The field nodeInfo.{} of node {} is empty",
+                            field, node_name
+                        )));
+                    }
+                }
+
+                result.check_eq(
+                    &format!("nodeInfo.operatingSystem of node {}", node_name),
+                    "linux",
+                    node_info.operating_system.as_str(),
+                );
+            }
+            None => result.combine::<(), _>(&Err(format!(
+                "The node {} does not report nodeInfo",
+                node_name
+            ))),
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn nodes_should_report_addresses_and_the_kubelet_endpoint() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.to_owned().unwrap_or_default();
+        let status = node.status.unwrap_or_default();
+        let addresses = status.addresses.unwrap_or_default();
+
+        let address = |address_type: &str| {
+            addresses
+                .iter()
+                .find(|address| address.type_ == address_type)
+                .map(|address| address.address.to_owned())
+        };
+
+        match address("InternalIP") {
+            Some(internal_ip) => {
+                if let Err(error) = internal_ip.parse::<IpAddr>() {
+                    result.combine::<(), _>(&Err(format!(
+                        "The InternalIP [{}] of node {} is not a valid IP address: {}",
+                        internal_ip, node_name, error
+                    )));
+                }
+            }
+            None => result.combine::<(), _>(&Err(format!(
+                "The node {} does not report an InternalIP",
+                node_name
+            ))),
+        }
+
+        if address("Hostname")
+            .filter(|hostname| !hostname.is_empty())
+            .is_none()
+        {
+            result.combine::<(), _>(&Err(format!(
+                "The node {} does not report a Hostname",
+                node_name
+            )));
+        }
+
+        let kubelet_port = status
+            .daemon_endpoints
+            .and_then(|daemon_endpoints| daemon_endpoints.kubelet_endpoint)
+            .map(|kubelet_endpoint| kubelet_endpoint.port);
+        if !matches!(kubelet_port, Some(1..=65535)) {
+            result.combine::<(), _>(&Err(format!(
+                "The node {} reports an invalid kubelet endpoint port: {:?}",
+                node_name, kubelet_port
+            )));
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn nodes_should_report_all_conditions() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.to_owned().unwrap_or_default();
+        let conditions = get_node_conditions(&node);
+
+        let mut condition_types = BTreeSet::new();
+        for condition in conditions.iter() {
+            if !condition_types.insert(condition.type_.as_str()) {
+                result.combine::<(), _>(&Err(format!(
+                    "The condition {} of node {} occurs more than once",
+                    condition.type_, node_name
+                )));
+            }
+            if condition.last_heartbeat_time.is_none() || condition.last_transition_time.is_none() {
+                result.combine::<(), _>(&Err(format!(
+                    "The condition {} of node {} does not contain the heartbeat and \
+                    transition times: {:?}",
+                    condition.type_, node_name, condition
+                )));
+            }
+        }
+
+        for condition_type in REQUIRED_CONDITIONS.iter() {
+            let expected_status = if *condition_type == "Ready" {
+                "True"
+            } else {
+                "False"
+            };
+            let status = conditions
+                .iter()
+                .find(|condition| condition.type_ == *condition_type)
+                .map(|condition| condition.status.as_str());
+            result.check_eq(
+                &format!(
+                    "status of condition {} of node {}",
+                    condition_type, node_name
+                ),
+                &Some(expected_status),
+                &status,
+            );
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn nodes_should_be_labeled() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    for node in stackable_nodes(&client).await? {
+        let node_name = node.metadata.name.to_owned().unwrap_or_default();
+        let labels = node.metadata.labels.to_owned().unwrap_or_default();
+
+        let hostname = node
+            .status
+            .as_ref()
+            .and_then(|status| status.addresses.as_ref())
+            .and_then(|addresses| addresses.iter().find(|address| address.type_ == "Hostname"))
+            .map(|address| address.address.as_str());
+
+        let expected_labels = [
+            ("kubernetes.io/arch", Some("stackable-linux")),
+            ("kubernetes.io/os", Some("linux")),
+            ("kubernetes.io/hostname", hostname),
+        ];
+
+        for (key, expected_value) in expected_labels.iter() {
+            result.check_eq(
+                &format!("label {} of node {}", key, node_name),
+                expected_value,
+                &labels.get(*key).map(String::as_str),
+            );
+        }
+
+        // The agent version label is optional but must be valid if set.
+        if let Some(agent_version) = labels.get(LABEL_AGENT_VERSION) {
+            result.combine(&agent_version.parse::<AgentVersion>().map_err(|error| {
+                anyhow!(
+                    "Label {} of node {} is not a valid agent version: {}",
+                    LABEL_AGENT_VERSION,
+                    node_name,
+                    error
+                )
+            }));
+        }
+    }
+
+    result.into()
+}
+
+/// Parses a Kubernetes quantity like `4`, `3500m`, `16Gi`, or `1e3`.
+///
+/// A quantity consists of a signed decimal number followed by a binary
+/// SI suffix, a decimal SI suffix, or a decimal exponent.
+fn parse_quantity(quantity: &str) -> Result<f64> {
+    const SUFFIXES: [(&str, f64); 14] = [
+        ("", 1.0),
+        ("Ki", 1024.0),
+        ("Mi", 1024.0 * 1024.0),
+        ("Gi", 1024.0 * 1024.0 * 1024.0),
+        ("Ti", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        ("Pi", 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        ("Ei", 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        ("m", 1e-3),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+        ("T", 1e12),
+        ("P", 1e15),
+        ("E", 1e18),
+    ];
+
+    let invalid = || anyhow!("[{}] is not a valid quantity", quantity);
+
+    let unsigned = quantity.trim_start_matches(|c| c == '+' || c == '-');
+    if quantity.len() - unsigned.len() > 1 {
+        return Err(invalid());
+    }
+    let number_len = quantity.len() - unsigned.len()
+        + unsigned
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(unsigned.len());
+    let (number, suffix) = quantity.split_at(number_len);
+
+    let number = number.parse::<f64>().map_err(|_| invalid())?;
+
+    let factor = match SUFFIXES.iter().find(|(name, _)| *name == suffix) {
+        Some((_, factor)) => *factor,
+        None => {
+            let exponent = suffix
+                .strip_prefix(|c| c == 'e' || c == 'E')
+                .and_then(|exponent| exponent.parse::<i32>().ok())
+                .ok_or_else(invalid)?;
+            10f64.powi(exponent)
+        }
+    };
+
+    Ok(number * factor)
 }
@@ -0,0 +1,101 @@
+mod util;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Result};
+use rstest::rstest;
+
+use crate::util::services::{
+    configurable_exit_service, echo_service, env_dump_job, exit_service, flaky_service,
+    forking_service, http_echo_service, log_flood_service, marker_job, marker_service,
+    noop_service, nostop_service, signal_recorder_service, ticker_service, unit_timestamps_job,
+};
+use crate::util::test_package::TestPackage;
+
+/// Line which starts a Python program embedded in a script
+const PYTHON_BEGIN: &str = "<<'EOF'\n";
+
+/// Line which ends a Python program embedded in a script
+const PYTHON_END: &str = "\nEOF\n";
+
+#[rstest]
+#[case::echo_service(echo_service())]
+#[case::exit_service(exit_service(1))]
+#[case::configurable_exit_service(configurable_exit_service())]
+#[case::noop_service(noop_service())]
+#[case::nostop_service(nostop_service())]
+#[case::http_echo_service(http_echo_service())]
+#[case::signal_recorder_service(signal_recorder_service())]
+#[case::forking_service(forking_service())]
+#[case::flaky_service(flaky_service())]
+#[case::env_dump_job(env_dump_job())]
+#[case::ticker_service(ticker_service())]
+#[case::log_flood_service(log_flood_service())]
+#[case::marker_job(marker_job())]
+#[case::marker_service(marker_service())]
+#[case::unit_timestamps_job(unit_timestamps_job())]
+fn package_script_should_be_valid(#[case] package: TestPackage) -> Result<()> {
+    if !package.script.starts_with("#!/bin/sh\n") {
+        return Err(anyhow!(
+            "The script of {} does not start with [#!/bin/sh]: {:?}",
+            package.name,
+            package.script.lines().next()
+        ));
+    }
+
+    run_with_input(&package.name, "sh", &["-n"], &package.script)?;
+
+    if let Some(python_program) = embedded_python_program(&package.script) {
+        run_with_input(
+            &package.name,
+            "python3",
+            &[
+                "-c",
+                "import sys; compile(sys.stdin.read(), 'script', 'exec')",
+            ],
+            python_program,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the Python program which is passed as here-document to the
+/// interpreter in the given script.
+fn embedded_python_program(script: &str) -> Option<&str> {
+    let start = script.find(PYTHON_BEGIN)? + PYTHON_BEGIN.len();
+    let length = script[start..].find(PYTHON_END)?;
+    Some(&script[start..start + length])
+}
+
+/// Runs the given command with the given input and returns an error if
+/// it does not succeed.
+fn run_with_input(package_name: &str, program: &str, args: &[&str], input: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| anyhow!("{} could not be started: {}", program, error))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("The standard input of {} is not available", program))?
+        .write_all(input.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "The script of {} is rejected by {}: {}",
+            package_name,
+            program,
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
@@ -0,0 +1,334 @@
+mod util;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::core::v1::{ExecAction, HTTPGetAction, Probe};
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use rstest::rstest;
+
+use crate::util::features::{feature_enabled, RESTART_COUNT};
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::http::{format_address, get, get_eventually, random_port};
+use crate::util::result::TestResult;
+use crate::util::services::http_echo_service;
+use crate::util::status::{condition_status, container_status, phase, verify_status};
+
+/// Period in which a started service must become reachable
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Period of the probes in seconds
+const PROBE_PERIOD_SECONDS: i32 = 1;
+
+/// Time to wait before it is assumed that a failing probe is
+/// evaluated
+const PROBE_SETTLING_TIME: Duration = Duration::from_secs(10);
+
+/// Seconds after which the http-echo-service becomes unhealthy in the
+/// liveness tests
+const UNHEALTHY_AFTER_SECONDS: u32 = 5;
+
+/// Creates a probe of the given kind.
+///
+/// An exec probe runs the given shell command, an HTTP probe requests
+/// the given path on the given port.
+fn probe(kind: &str, command: &str, path: &str, port: u16) -> Probe {
+    let mut probe = Probe {
+        period_seconds: Some(PROBE_PERIOD_SECONDS),
+        failure_threshold: Some(1),
+        success_threshold: Some(1),
+        timeout_seconds: Some(1),
+        ..Default::default()
+    };
+
+    match kind {
+        "exec" => {
+            probe.exec = Some(ExecAction {
+                command: Some(vec![
+                    String::from("/bin/sh"),
+                    String::from("-c"),
+                    String::from(command),
+                ]),
+            })
+        }
+        "http" => {
+            probe.http_get = Some(HTTPGetAction {
+                path: Some(String::from(path)),
+                port: IntOrString::Int(port.into()),
+                ..Default::default()
+            })
+        }
+        other => panic!("invalid parameter: {}", other),
+    }
+
+    probe
+}
+
+/// Returns true if the Ready condition of the given pod is `True`.
+fn is_ready(pod: &Pod) -> bool {
+    condition_status(pod, "Ready").as_deref() == Some("True")
+}
+
+#[rstest]
+#[case::exec_probe("exec")]
+#[case::http_probe("http")]
+#[tokio::test]
+async fn ready_condition_should_follow_the_readiness_probe(#[case] kind: &str) -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let port = random_port();
+    let ready_file = format!("/tmp/{}.ready", unique_name("http-echo-service"));
+
+    let service = http_echo_service();
+    let mut pod_definition = service.pod_with_env(
+        &unique_name("agent-probes-integration-test-readiness"),
+        &[
+            ("PORT", port.to_string().as_str()),
+            ("READY_FILE", ready_file.as_str()),
+        ],
+    );
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        for container in spec.containers.iter_mut() {
+            container.readiness_probe = Some(probe(
+                kind,
+                &format!("test -f '{}'", ready_file),
+                "/ready",
+                port,
+            ));
+        }
+    }
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let running = verify_status(&client, pod, |pod| phase(pod) == "Running").await;
+        result.combine(&running);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        let base_uri = get_status_result
+            .ok()
+            .and_then(|pod| pod.status)
+            .and_then(|status| status.host_ip)
+            .ok_or_else(|| anyhow!("The host IP is not set"))
+            .and_then(|host_ip| format_address(&host_ip, port))
+            .map(|address| format!("http://{}", address));
+        result.combine(&base_uri);
+
+        if let Ok(base_uri) = base_uri {
+            let service_started =
+                get_eventually(&format!("{}/identity", base_uri), CONNECTION_TIMEOUT).await;
+            result.combine(&service_started);
+
+            let verify_result = verify_readiness_toggling(&client, pod, &base_uri).await;
+            result.combine(&verify_result);
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+/// Toggles the readiness of the running http-echo-service and verifies
+/// that the Ready condition follows.
+async fn verify_readiness_toggling(client: &KubeClient, pod: &Pod, base_uri: &str) -> Result<()> {
+    // The service starts as not ready.
+    tokio::time::sleep(PROBE_SETTLING_TIME).await;
+    if is_ready(&client.get_status(pod).await?) {
+        return Err(anyhow!(
+            "The pod is ready although its readiness probe fails; the agent does not seem to \
+            evaluate readiness probes"
+        ));
+    }
+
+    get(&format!("{}/ready/on", base_uri)).await?;
+    verify_status(client, pod, is_ready)
+        .await
+        .map_err(|error| {
+            anyhow!(
+                "The pod did not become ready after its readiness probe succeeded: {}",
+                error
+            )
+        })?;
+
+    get(&format!("{}/ready/off", base_uri)).await?;
+    verify_status(client, pod, |pod| !is_ready(pod))
+        .await
+        .map_err(|error| {
+            anyhow!(
+                "The pod stayed ready after its readiness probe failed: {}",
+                error
+            )
+        })?;
+
+    // A failing readiness probe must neither stop nor restart the
+    // service.
+    let pod = client.get_status(pod).await?;
+    let container_status = container_status(&pod, &http_echo_service().name)
+        .ok_or_else(|| anyhow!("The container status is missing"))?;
+    if container_status.ready {
+        return Err(anyhow!(
+            "The container is ready although its readiness probe fails"
+        ));
+    }
+    if container_status.restart_count != 0 || phase(&pod) != "Running" {
+        return Err(anyhow!(
+            "The service was stopped or restarted because of a failing readiness probe: {:?}",
+            container_status
+        ));
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case::exec_probe_with_restart_policy_always("exec", "Always", "expect_restart")]
+#[case::exec_probe_with_restart_policy_onfailure("exec", "OnFailure", "expect_restart")]
+#[case::exec_probe_with_restart_policy_never("exec", "Never", "expect_no_restart")]
+#[case::http_probe_with_restart_policy_always("http", "Always", "expect_restart")]
+#[case::http_probe_with_restart_policy_onfailure("http", "OnFailure", "expect_restart")]
+#[case::http_probe_with_restart_policy_never("http", "Never", "expect_no_restart")]
+#[tokio::test]
+async fn unhealthy_service_should_be_terminated_according_to_the_liveness_probe(
+    #[case] kind: &str,
+    #[case] restart_policy: &str,
+    #[case] expected_behavior: &str,
+) -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let port = random_port();
+    let healthy_file = format!("/tmp/{}.healthy", unique_name("http-echo-service"));
+
+    let service = http_echo_service();
+    let mut pod_definition = service.pod_with_env(
+        &unique_name("agent-probes-integration-test-liveness"),
+        &[
+            ("PORT", port.to_string().as_str()),
+            ("HEALTHY_FILE", healthy_file.as_str()),
+            (
+                "UNHEALTHY_AFTER",
+                UNHEALTHY_AFTER_SECONDS.to_string().as_str(),
+            ),
+        ],
+    );
+    if let Some(spec) = pod_definition.spec.as_mut() {
+        spec.restart_policy = Some(String::from(restart_policy));
+        for container in spec.containers.iter_mut() {
+            container.liveness_probe = Some(probe(
+                kind,
+                &format!("test -f '{}'", healthy_file),
+                "/healthy",
+                port,
+            ));
+        }
+    }
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await {
+            let verify_result = match expected_behavior {
+                "expect_restart" => verify_restart_on_liveness_failure(&client, pod).await,
+                "expect_no_restart" => verify_termination_on_liveness_failure(&client, pod).await,
+                other => panic!("invalid parameter: {}", other),
+            };
+            result.combine(&verify_result);
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
+}
+
+/// Verifies that the service is restarted repeatedly because it becomes
+/// unhealthy after every start.
+async fn verify_restart_on_liveness_failure(client: &KubeClient, pod: &Pod) -> Result<()> {
+    let container_name = http_echo_service().name;
+
+    verify_status(client, pod, {
+        let container_name = container_name.to_owned();
+        move |pod| {
+            container_status(pod, &container_name)
+                .filter(|container_status| container_status.restart_count >= 2)
+                .is_some()
+        }
+    })
+    .await
+    .map_err(|error| {
+        anyhow!(
+            "The unhealthy service was not restarted; the agent does not seem to evaluate \
+                liveness probes: {}",
+            error
+        )
+    })?;
+
+    let pod = client.get_status(pod).await?;
+    let terminated = container_status(&pod, &container_name)
+        .and_then(|container_status| container_status.last_state.as_ref())
+        .and_then(|last_state| last_state.terminated.as_ref())
+        .ok_or_else(|| anyhow!("The last state of the restarted container is not recorded"))?;
+
+    verify_termination_reason(terminated)
+}
+
+/// Verifies that the service is terminated but not restarted after it
+/// became unhealthy.
+async fn verify_termination_on_liveness_failure(client: &KubeClient, pod: &Pod) -> Result<()> {
+    let container_name = http_echo_service().name;
+
+    verify_status(client, pod, |pod| phase(pod) == "Failed")
+        .await
+        .map_err(|error| {
+            anyhow!(
+                "The unhealthy service was not terminated; the agent does not seem to \
+                evaluate liveness probes: {}",
+                error
+            )
+        })?;
+
+    let pod = client.get_status(pod).await?;
+    let container_status = container_status(&pod, &container_name)
+        .ok_or_else(|| anyhow!("The container status is missing"))?;
+
+    if container_status.restart_count != 0 {
+        return Err(anyhow!(
+            "The service was restarted {} times although the restart policy is Never",
+            container_status.restart_count
+        ));
+    }
+
+    let terminated = container_status
+        .state
+        .as_ref()
+        .and_then(|state| state.terminated.as_ref())
+        .ok_or_else(|| anyhow!("The container is not in the terminated state"))?;
+
+    verify_termination_reason(terminated)
+}
+
+/// Verifies that a container which was killed because of a failing
+/// liveness probe records a non-zero exit code and a reason.
+fn verify_termination_reason(terminated: &ContainerStateTerminated) -> Result<()> {
+    if terminated.exit_code == 0 {
+        return Err(anyhow!(
+            "The container killed by the liveness probe terminated with exit code 0: {:?}",
+            terminated
+        ));
+    }
+    if terminated.reason.as_deref().unwrap_or_default().is_empty() {
+        return Err(anyhow!(
+            "No termination reason is recorded for the container killed by the liveness \
+            probe: {:?}",
+            terminated
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+mod util;
+
+use anyhow::Result;
+use integration_test_commons::test::prelude::*;
+use uuid::Uuid;
+
+use crate::util::features::{feature_enabled, RESTART_COUNT};
+use crate::util::fixture::{
+    close_repository, create_pod, delete_pod, start_repository, unique_name,
+};
+use crate::util::probe::{node_name, run_probe};
+use crate::util::result::TestResult;
+use crate::util::services::forking_service;
+use crate::util::status::{verify_pod_condition, verify_status};
+use crate::util::test_package::TestPackage;
+
+/// Name of the environment variable which marks the process tree
+const MARKER_KEY: &str = "PROCESS_TREE_MARKER";
+
+/// Number of processes in the tree of the forking-service
+const PROCESS_TREE_SIZE: usize = 6;
+
+/// Minimum number of processes in the tree of a running
+/// forking-service
+///
+/// Short-living processes and processes which are just being spawned
+/// are not taken into account.
+const MIN_RUNNING_PROCESSES: usize = 4;
+
+#[tokio::test]
+async fn forked_processes_should_be_terminated_when_the_pod_is_deleted() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let marker = Uuid::new_v4().to_string();
+
+    let service = forking_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-process-tree-integration-test-deletion"),
+        &[(MARKER_KEY, marker.as_str())],
+    );
+
+    let repository_result = start_repository(&client, &mut result, &[&service]).await;
+    let pod_result = create_pod(&client, &mut result, &pod_definition).await;
+
+    let mut node = None;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+        node = get_status_result.ok().as_ref().and_then(node_name);
+    }
+
+    if let Some(node) = &node {
+        verify_marked_processes(
+            &client,
+            &mut result,
+            node,
+            &marker,
+            MIN_RUNNING_PROCESSES,
+            PROCESS_TREE_SIZE,
+            "The process tree of the running service is incomplete",
+        )
+        .await;
+    }
+
+    delete_pod(&client, &mut result, pod_result).await;
+
+    if let Some(node) = &node {
+        verify_marked_processes(
+            &client,
+            &mut result,
+            node,
+            &marker,
+            0,
+            0,
+            "Processes of the service survived the deletion of the pod",
+        )
+        .await;
+    }
+
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
+}
+
+#[tokio::test]
+async fn forked_processes_should_be_terminated_when_the_service_is_restarted() -> Result<()> {
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+
+    let marker = Uuid::new_v4().to_string();
+
+    // The service terminates after some seconds and is restarted while
+    // its forked processes are still running.
+    let service = forking_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-process-tree-integration-test-restart"),
+        &[(MARKER_KEY, marker.as_str()), ("EXIT_AFTER", "5")],
+    );
+
+    let repository_result = start_repository(&client, &mut result, &[&service]).await;
+    let pod_result = create_pod(&client, &mut result, &pod_definition).await;
+
+    let mut node = None;
+    let mut restarted = false;
+
+    if let Ok(pod) = &pod_result {
+        let running = verify_status(&client, pod, |pod| {
+            pod.status
+                .as_ref()
+                .and_then(|pod_status| pod_status.phase.as_deref())
+                == Some("Running")
+        })
+        .await;
+        result.combine(&running);
+
+        // Without restart counts the restarts cannot be awaited, but the
+        // deletion of the pod is verified nevertheless.
+        if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await {
+            let restarted_result = verify_status(&client, pod, |pod| {
+                pod.status
+                    .as_ref()
+                    .and_then(|pod_status| pod_status.container_statuses.as_ref())
+                    .and_then(|container_statuses| container_statuses.first())
+                    .filter(|container_status| container_status.restart_count >= 2)
+                    .is_some()
+            })
+            .await;
+            result.combine(&restarted_result);
+            restarted = restarted_result.is_ok();
+        }
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+        node = get_status_result.ok().as_ref().and_then(node_name);
+    }
+
+    if let (Some(node), true) = (&node, restarted) {
+        // If the processes of former runs survived then more processes
+        // than in one tree are found.
+        verify_marked_processes(
+            &client,
+            &mut result,
+            node,
+            &marker,
+            0,
+            PROCESS_TREE_SIZE,
+            "Processes of the service survived the restart of the service",
+        )
+        .await;
+    }
+
+    delete_pod(&client, &mut result, pod_result).await;
+
+    if let Some(node) = &node {
+        verify_marked_processes(
+            &client,
+            &mut result,
+            node,
+            &marker,
+            0,
+            0,
+            "Processes of the service survived the deletion of the pod",
+        )
+        .await;
+    }
+
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
+}
+
+/// Verifies with a probe job that the number of processes on the given
+/// node which carry the given marker in their environment is within the
+/// given bounds.
+async fn verify_marked_processes(
+    client: &KubeClient,
+    result: &mut TestResult,
+    node: &str,
+    marker: &str,
+    min: usize,
+    max: usize,
+    error_message: &str,
+) {
+    let probe = process_probe(marker, min, max);
+
+    if let Some(probe_output) = run_probe(client, result, &probe, node).await {
+        if !probe_output.succeeded {
+            result.combine::<(), _>(&Err(format!(
+                "{}; expected between {} and {} marked processes; probe output: {:?}",
+                error_message,
+                min,
+                max,
+                probe_output.logs.unwrap_or_default()
+            )));
+        }
+    }
+}
+
+/// Creates a job which lists all processes carrying the given marker in
+/// their environment and succeeds if their number is within the given
+/// bounds.
+///
+/// The job must run as root to read the environment of foreign
+/// processes.
+fn process_probe(marker: &str, min: usize, max: usize) -> TestPackage {
+    TestPackage {
+        name: unique_name("process-probe"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: formatdoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            count=0
+
+            for environ in /proc/[0-9]*/environ; do
+                if tr '\0' '\n' < "$environ" 2> /dev/null | grep -qx "{marker_key}={marker}"; then
+                    pid=${{environ#/proc/}}
+                    pid=${{pid%/environ}}
+                    echo "process $pid: $(tr '\0' ' ' < "/proc/$pid/cmdline" 2> /dev/null)"
+                    count=$((count + 1))
+                fi
+            done
+
+            echo "$count marked processes found"
+
+            test "$count" -ge {min} && test "$count" -le {max}
+            "#,
+            marker_key = MARKER_KEY,
+            marker = marker,
+            min = min,
+            max = max
+        ),
+    }
+}
@@ -7,6 +7,7 @@ use uuid::Uuid;
 use crate::util::repository::StackableRepositoryBuilder;
 use crate::util::result::TestResult;
 use crate::util::services::noop_service;
+use crate::util::status::verify_pod_condition;
 
 #[tokio::test]
 async fn invalid_or_unreachable_repositories_should_be_ignored() -> Result<()> {
@@ -56,7 +57,7 @@ async fn invalid_or_unreachable_repositories_should_be_ignored() -> Result<()> {
     // Verify that the pod was downloaded, started, and is ready
 
     if let Ok(pod) = &pod_result {
-        let pod_ready = client.verify_pod_condition(pod, "Ready").await;
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
         result.combine(&pod_ready);
     }
 
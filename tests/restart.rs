@@ -1,16 +1,20 @@
 mod util;
 
-use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
 use integration_test_commons::test::prelude::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
 use rstest::rstest;
 use util::repository::StackableRepositoryInstance;
 use util::result::TestResult;
-use util::services::exit_service;
-use uuid::Uuid;
-
-use crate::util::repository::StackableRepositoryBuilder;
+use util::services::{exit_service, flaky_service};
+use util::test_package::TestPackage;
 
-const ANNOTATION_KEY_FEATURE_RESTART_COUNT: &str = "featureRestartCount";
+use crate::util::features::{feature_enabled, RESTART_COUNT};
+use crate::util::fixture::{set_up, tear_down, unique_name};
+use crate::util::probe::{node_name, run_probe};
+use crate::util::status::{verify_pod_condition, verify_status, TimestampValidator};
 
 #[rstest]
 #[case::failing_service_should_be_restarted_on_restart_policy_always(
@@ -52,7 +56,7 @@ async fn service_should_be_restarted_according_to_the_restart_policy(
     let client = KubeClient::new().await?;
     let mut result = TestResult::default();
 
-    let (repository_result, pod_result) = set_up(
+    let (repository_result, pod_result) = set_up_service(
         &client,
         &mut result,
         match service {
@@ -65,7 +69,7 @@ async fn service_should_be_restarted_according_to_the_restart_policy(
     .await;
 
     if let Ok(pod) = &pod_result {
-        if restart_count_enabled(&client, &mut result, pod).await {
+        if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await {
             match expected_behavior {
                 "expect_restart" => verify_restart(&client, &mut result, pod).await,
                 "expect_no_restart" => verify_no_restart(&client, &mut result, pod).await,
@@ -79,7 +83,7 @@ async fn service_should_be_restarted_according_to_the_restart_policy(
     result.into()
 }
 
-async fn set_up(
+async fn set_up_service(
     client: &KubeClient,
     result: &mut TestResult,
     succeeding: bool,
@@ -87,89 +91,35 @@ async fn set_up(
 ) -> (Result<StackableRepositoryInstance>, Result<Pod>) {
     let service = exit_service(if succeeding { 0 } else { 1 });
 
-    let repository_name = format!("restart-test-repository-{}", Uuid::new_v4());
-    let repository_result = StackableRepositoryBuilder::new(&repository_name)
-        .package(&service)
-        .run(client)
-        .await;
-    result.combine(&repository_result);
-
-    let mut pod_definition = service.pod(&format!(
-        "agent-service-integration-test-restart-{}",
-        Uuid::new_v4()
-    ));
+    let mut pod_definition = service.pod(&unique_name("agent-service-integration-test-restart"));
     pod_definition
         .spec
         .get_or_insert_with(Default::default)
         .restart_policy
         .replace(String::from(restart_policy));
 
-    let pod_result = client
-        .create(&serde_yaml::to_string(&pod_definition).unwrap())
-        .await;
-    result.combine(&pod_result);
-
-    (repository_result, pod_result)
-}
-
-async fn tear_down(
-    client: &KubeClient,
-    result: &mut TestResult,
-    repository_result: Result<StackableRepositoryInstance>,
-    pod_result: Result<Pod>,
-) {
-    if let Ok(pod) = pod_result {
-        let deletion_result = client.delete(pod).await;
-        result.combine(&deletion_result);
-    }
-    if let Ok(repository) = repository_result {
-        let close_result = repository.close(client).await;
-        result.combine(&close_result);
-    }
-}
-
-async fn restart_count_enabled(client: &KubeClient, result: &mut TestResult, pod: &Pod) -> bool {
-    let get_annotation_result = client
-        .get_annotation::<Pod>(pod, ANNOTATION_KEY_FEATURE_RESTART_COUNT)
-        .await;
-    result.combine(&get_annotation_result);
-
-    match get_annotation_result.as_deref() {
-        Ok("true") => true,
-        Ok("false") => false,
-        Ok(value) => {
-            result.combine::<(), _>(&Err(format!(
-                "Pod annotation [{}] contains unknown value [{}]; \
-                expected [true] or [false]",
-                ANNOTATION_KEY_FEATURE_RESTART_COUNT, value,
-            )));
-            false
-        }
-        _ => false,
-    }
+    set_up(client, result, &[&service], &pod_definition).await
 }
 
 async fn verify_restart(client: &KubeClient, result: &mut TestResult, pod: &Pod) {
-    let verify_status_result = client
-        .verify_status(pod, |pod| {
-            pod.status
-                .as_ref()
-                .and_then(|pod_status| pod_status.container_statuses.as_ref())
-                .and_then(|container_statuses| container_statuses.first())
-                .filter(|container_status| container_status.restart_count > 3)
-                .is_some()
-        })
-        .await;
+    let verify_status_result = verify_status(client, pod, |pod| {
+        pod.status
+            .as_ref()
+            .and_then(|pod_status| pod_status.container_statuses.as_ref())
+            .and_then(|container_statuses| container_statuses.first())
+            .filter(|container_status| container_status.restart_count > 3)
+            .is_some()
+    })
+    .await;
     result.combine(&verify_status_result);
 }
 
 async fn verify_no_restart(client: &KubeClient, result: &mut TestResult, pod: &Pod) {
-    let verify_status_result = client
-        .verify_status(pod, |pod| {
-            let phase = pod.status.as_ref().and_then(|status| status.phase.as_ref());
-            phase == Some(&String::from("Succeeded")) || phase == Some(&String::from("Failed"))
-        })
-        .await;
+    let verify_status_result = verify_status(client, pod, |pod| {
+        let phase = pod.status.as_ref().and_then(|status| status.phase.as_ref());
+        phase == Some(&String::from("Succeeded")) || phase == Some(&String::from("Failed"))
+    })
+    .await;
     result.combine(&verify_status_result);
 
     let get_status_result = client.get_status(pod).await;
@@ -186,3 +136,232 @@ async fn verify_no_restart(client: &KubeClient, result: &mut TestResult, pod: &P
         result.combine(&restart_count_result);
     }
 }
+
+/// Upper bound for the delay before the restart of a failed container
+///
+/// The backoff delays are chosen by the agent, only this upper bound
+/// is given by the maximum CrashLoopBackOff delay of Kubernetes.
+const BACKOFF_MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Tolerance for the observed restart delays
+///
+/// The timestamps in the container states have a resolution of one
+/// second.
+const BACKOFF_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// Interval in which the pod status is polled while observing restarts
+const RESTART_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Restarts observed while a failing container becomes ready
+struct RestartObservation {
+    /// Pairs of the termination of a run and the start of the directly
+    /// following run in the order of their occurrence
+    restarts: Vec<(Time, Time)>,
+    restart_count: i32,
+}
+
+impl RestartObservation {
+    /// Returns the delays between the termination of a run and the start
+    /// of the next run.
+    fn restart_delays(&self) -> Vec<Duration> {
+        self.restarts
+            .iter()
+            .filter_map(|(finished_at, started_at)| (started_at.0 - finished_at.0).to_std().ok())
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn failing_service_should_become_ready_after_restarts_with_backoff() -> Result<()> {
+    const FAILING_STARTS: i32 = 3;
+
+    let client = KubeClient::new().await?;
+    let mut result = TestResult::default();
+    let timestamp_validator = TimestampValidator::new();
+
+    let counter_file = format!("/tmp/{}.counter", unique_name("flaky-service"));
+
+    let service = flaky_service();
+    let pod_definition = service.pod_with_env(
+        &unique_name("agent-service-integration-test-backoff"),
+        &[
+            ("COUNTER_FILE", counter_file.as_str()),
+            ("FAILING_STARTS", FAILING_STARTS.to_string().as_str()),
+        ],
+    );
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    let mut node = None;
+
+    if let Ok(pod) = &pod_result {
+        if feature_enabled(&client, &mut result, pod, RESTART_COUNT).await {
+            let observation_result = observe_until_ready(&client, pod, FAILING_STARTS).await;
+            result.combine(&observation_result);
+
+            if let Ok(observation) = observation_result {
+                result.check_eq("restart count", &FAILING_STARTS, &observation.restart_count);
+
+                let delays = observation.restart_delays();
+
+                if delays.is_empty() {
+                    result.combine::<(), _>(&Err(
+                        "No delay between a termination and the following restart was observed",
+                    ));
+                }
+
+                for delay in delays.iter() {
+                    if *delay > BACKOFF_MAX_DELAY + BACKOFF_TOLERANCE {
+                        result.combine::<(), _>(&Err(format!(
+                            "A restart took place {:?} after the termination but the backoff \
+                            delay must not exceed {:?}",
+                            delay, BACKOFF_MAX_DELAY
+                        )));
+                    }
+                }
+
+                for (delay, next_delay) in delays.iter().zip(delays.iter().skip(1)) {
+                    if *next_delay + BACKOFF_TOLERANCE < *delay {
+                        result.combine::<(), _>(&Err(format!(
+                            "The backoff delays must not decrease but a restart delay of {:?} \
+                            was followed by a delay of {:?}; observed delays: {:?}",
+                            delay, next_delay, delays
+                        )));
+                    }
+                }
+            }
+
+            let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+            result.combine(&pod_ready);
+
+            let get_status_result = client.get_status(pod).await;
+            result.combine(&get_status_result);
+
+            if let Ok(pod) = get_status_result {
+                result.combine(&timestamp_validator.validate(&pod));
+            }
+        }
+
+        // The service ran on the node regardless of the checks above.
+        node = client
+            .get_status(pod)
+            .await
+            .ok()
+            .and_then(|pod| node_name(&pod));
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    if let Some(node) = node {
+        let cleanup = run_probe(
+            &client,
+            &mut result,
+            &file_removal_job(&counter_file),
+            &node,
+        )
+        .await;
+        if let Some(cleanup) = cleanup {
+            if !cleanup.succeeded {
+                result.combine::<(), _>(&Err(format!(
+                    "The counter file [{}] could not be removed",
+                    counter_file
+                )));
+            }
+        }
+    }
+
+    result.into()
+}
+
+/// Creates a job which removes the given file.
+fn file_removal_job(path: &str) -> TestPackage {
+    TestPackage {
+        name: unique_name("file-removal-job"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: formatdoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            rm -f "{path}"
+            "#,
+            path = path
+        ),
+    }
+}
+
+/// Polls the status of the given pod until its container is ready
+/// after at least `min_restarts` restarts and records the restarts of
+/// the former runs.
+///
+/// Failing runs may be ready for a short time, so the readiness alone
+/// does not indicate that the container keeps running.
+///
+/// A restart is recorded if the container status contains the
+/// termination of the previous run in `lastState` and the start of the
+/// current run in `state`. Both are taken from the same status, so a
+/// run which terminates and restarts between two polls is skipped
+/// instead of being paired with a wrong start.
+async fn observe_until_ready(
+    client: &KubeClient,
+    pod: &Pod,
+    min_restarts: i32,
+) -> Result<RestartObservation> {
+    const TIMEOUT: Duration = Duration::from_secs(600);
+
+    let start = Instant::now();
+    let mut restarts: Vec<(Time, Time)> = Vec::new();
+
+    loop {
+        let pod = client.get_status(pod).await?;
+
+        let container_status = pod
+            .status
+            .and_then(|pod_status| pod_status.container_statuses)
+            .and_then(|container_statuses| container_statuses.into_iter().next());
+
+        if let Some(container_status) = container_status {
+            let last_finished_at = container_status
+                .last_state
+                .and_then(|last_state| last_state.terminated)
+                .and_then(|terminated| terminated.finished_at);
+            let started_at = container_status.state.and_then(|state| {
+                let running_since = state.running.and_then(|running| running.started_at);
+                running_since.or(state
+                    .terminated
+                    .and_then(|terminated| terminated.started_at))
+            });
+
+            if let (Some(last_finished_at), Some(started_at)) = (last_finished_at, started_at) {
+                let already_recorded = restarts
+                    .last()
+                    .filter(|(recorded, _)| *recorded == last_finished_at)
+                    .is_some();
+                if !already_recorded {
+                    restarts.push((last_finished_at, started_at));
+                }
+            }
+
+            if container_status.ready && container_status.restart_count >= min_restarts {
+                return Ok(RestartObservation {
+                    restarts,
+                    restart_count: container_status.restart_count,
+                });
+            }
+        }
+
+        if start.elapsed() > TIMEOUT {
+            return Err(anyhow!(
+                "The container did not become ready after {} restarts within {:?}",
+                min_restarts,
+                TIMEOUT
+            ));
+        }
+
+        tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+    }
+}
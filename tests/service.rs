@@ -1,71 +1,61 @@
-use futures::future::join_all;
+mod util;
+
+use anyhow::Result;
 use integration_test_commons::test::prelude::*;
-use std::{fmt, time::Duration};
+use std::time::Duration;
+
+use crate::util::benchmark::BenchmarkReport;
+use crate::util::fixture::{
+    close_repository, create_pod, delete_pod, set_up, start_repository, tear_down, unique_name,
+};
+use crate::util::result::TestResult;
+use crate::util::scale::{run_scale_test, ScaleConfig, ScaleReport};
+use crate::util::services::{noop_service, nostop_service};
+use crate::util::status::{verify_pod_condition, verify_status, TimestampValidator};
+
+#[tokio::test]
+async fn service_should_be_started_successfully() -> Result<()> {
+    let mut client = KubeClient::new().await?;
+    client.timeouts.delete = Duration::from_secs(60);
 
-#[test]
-fn service_should_be_started_successfully() {
-    let mut client = TestKubeClient::new();
-    client.timeouts().delete = Duration::from_secs(60);
+    let mut result = TestResult::default();
+    let timestamp_validator = TimestampValidator::new();
 
-    setup_repository(&client);
+    let service = noop_service();
+    let pod_definition = service.pod(&unique_name("agent-service-integration-test-start"));
 
-    let pod = TemporaryResource::new(
-        &client,
-        &with_unique_name(indoc!(
-            "
-            apiVersion: v1
-            kind: Pod
-            metadata:
-              name: agent-service-integration-test-start
-            spec:
-              containers:
-                - name: noop-service
-                  image: noop-service:1.0.0
-                  command:
-                    - noop-service-1.0.0/start.sh
-              nodeSelector:
-                kubernetes.io/arch: stackable-linux
-              tolerations:
-                - key: kubernetes.io/arch
-                  operator: Equal
-                  value: stackable-linux
-            "
-        )),
-    );
-
-    client.verify_pod_condition(&pod, "Ready");
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let Ok(pod) = get_status_result {
+            result.combine(&timestamp_validator.validate(&pod));
+        }
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
 }
 
-#[test]
-fn host_ip_and_node_ip_should_be_set() {
-    let mut client = TestKubeClient::new();
-    client.timeouts().delete = Duration::from_secs(60);
+#[tokio::test]
+async fn host_ip_and_node_ip_should_be_set() -> Result<()> {
+    let mut client = KubeClient::new().await?;
+    client.timeouts.delete = Duration::from_secs(60);
 
-    setup_repository(&client);
+    let mut result = TestResult::default();
 
-    let pod = TemporaryResource::new(
-        &client,
-        &with_unique_name(indoc!(
-            "
-            apiVersion: v1
-            kind: Pod
-            metadata:
-              name: agent-service-integration-test-ip
-            spec:
-              containers:
-                - name: noop-service
-                  image: noop-service:1.0.0
-                  command:
-                    - noop-service-1.0.0/start.sh
-              nodeSelector:
-                kubernetes.io/arch: stackable-linux
-              tolerations:
-                - key: kubernetes.io/arch
-                  operator: Equal
-                  value: stackable-linux
-            "
-        )),
-    );
+    let service = noop_service();
+    let pod_definition = service.pod(&unique_name("agent-service-integration-test-ip"));
+
+    let (repository_result, pod_result) =
+        set_up(&client, &mut result, &[&service], &pod_definition).await;
 
     let are_host_ip_and_node_ip_set = |pod: &Pod| {
         let host_ip = pod
@@ -80,158 +70,139 @@ fn host_ip_and_node_ip_should_be_set() {
         host_ip.is_some() && pod_ip.is_some() && host_ip == pod_ip
     };
 
-    client.verify_status::<Pod, _>(&pod, are_host_ip_and_node_ip_set);
+    if let Ok(pod) = &pod_result {
+        let verify_status_result = verify_status(&client, pod, are_host_ip_and_node_ip_set).await;
+        result.combine(&verify_status_result);
+    }
+
+    tear_down(&client, &mut result, repository_result, pod_result).await;
+
+    result.into()
 }
 
-#[test]
-fn restart_after_ungraceful_shutdown_should_succeed() {
+#[tokio::test]
+async fn restart_after_ungraceful_shutdown_should_succeed() -> Result<()> {
     // must be greater than the period between the deletion of the pod
     // and the creation of the new systemd service
     let termination_grace_period = Duration::from_secs(5);
 
-    let mut client = TestKubeClient::new();
+    let mut client = KubeClient::new().await?;
     // delete must await the end of the termination grace period
-    client.timeouts().delete = Duration::from_secs(60) + termination_grace_period;
-
-    setup_repository(&client);
-
-    let pod_spec = with_unique_name(&formatdoc!(
-        "
-        apiVersion: v1
-        kind: Pod
-        metadata:
-          name: agent-service-integration-test-restart
-        spec:
-          containers:
-            - name: nostop-service
-              image: nostop-service:1.0.1
-              command:
-                - nostop-service-1.0.1/start.sh
-          nodeSelector:
-            kubernetes.io/arch: stackable-linux
-          tolerations:
-            - key: kubernetes.io/arch
-              operator: Equal
-              value: stackable-linux
-          terminationGracePeriodSeconds: {termination_grace_period_seconds}
-        ",
-        termination_grace_period_seconds = termination_grace_period.as_secs()
-    ));
+    client.timeouts.delete = Duration::from_secs(60) + termination_grace_period;
+
+    let mut result = TestResult::default();
+
+    let service = nostop_service();
+
+    // Both pods must have the same name so that the second pod is
+    // started with the same systemd unit name as the first one.
+    let mut pod_definition = service.pod(&unique_name("agent-service-integration-test-restart"));
+    pod_definition
+        .spec
+        .get_or_insert_with(Default::default)
+        .termination_grace_period_seconds
+        .replace(termination_grace_period.as_secs() as i64);
+
+    let repository_result = start_repository(&client, &mut result, &[&service]).await;
 
     for _ in 1..=2 {
-        let pod = TemporaryResource::new(&client, &pod_spec);
-        client.verify_pod_condition(&pod, "Ready");
+        let pod_result = create_pod(&client, &mut result, &pod_definition).await;
+
+        if let Ok(pod) = &pod_result {
+            let pod_ready = verify_pod_condition(&client, pod, "Ready").await;
+            result.combine(&pod_ready);
+        }
+
+        delete_pod(&client, &mut result, pod_result).await;
     }
+
+    close_repository(&client, &mut result, repository_result).await;
+
+    result.into()
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn starting_and_stopping_100_pods_simultaneously_should_succeed() {
-    let mut client = KubeClient::new()
-        .await
-        .expect("Kubernetes client could not be created");
-    client.timeouts.create = Duration::from_secs(60);
-    client.timeouts.delete = Duration::from_secs(60);
-    client.timeouts.verify_status = Duration::from_secs(60);
+async fn starting_and_stopping_100_pods_simultaneously_should_succeed() -> Result<()> {
+    let client = scale_test_client().await?;
+
+    let config = ScaleConfig {
+        pods: 100,
+        concurrency: 100,
+        waves: 1,
+        churn_rate: 0.0,
+        duration: Duration::ZERO,
+    };
 
-    setup_repository_async(&client)
-        .await
-        .expect("Repository could not be setup.");
-
-    const NUM_PODS: u32 = 100;
-
-    let node = client
-        .list_labeled::<Node>("kubernetes.io/arch=stackable-linux")
-        .await
-        .expect("List of Stackable nodes could not be retrieved")
-        .into_iter()
-        .next()
-        .expect("No Stackable node found");
-
-    let node_name = node.metadata.name.clone().expect("Node has no name");
-
-    let allocatable_pods = get_allocatable_pods(&node);
-
-    // This assertion assumes that either the allocated pods are already
-    // subtracted from `allocatable_pods` (which is currently not the
-    // case) or that no other pods are started while testing.
-    assert!(
-        NUM_PODS <= allocatable_pods,
-        "The test case tries to create {num} pods but only {max} pods \
-        are allocatable on the node {node_name}.",
-        num = NUM_PODS,
-        max = allocatable_pods,
-        node_name = node_name
-    );
-
-    let pod_spec = formatdoc!(
-        "
-        apiVersion: v1
-        kind: Pod
-        metadata:
-          name: agent-service-integration-test-race-condition
-        spec:
-          containers:
-            - name: noop-service
-              image: noop-service:1.0.0
-              command:
-                - noop-service-1.0.0/start.sh
-          nodeSelector:
-            kubernetes.io/arch: stackable-linux
-          tolerations:
-            - key: kubernetes.io/arch
-              operator: Equal
-              value: stackable-linux
-          nodeName: {node_name}
-        ",
-        node_name = node_name
-    );
-
-    let pod_specs = (0..NUM_PODS)
-        .map(|_| with_unique_name(&pod_spec))
-        .collect::<Vec<_>>();
-
-    let (pods, creation_errors) =
-        partition_results(join_all(pod_specs.iter().map(|spec| client.create::<Pod>(spec))).await);
-    let pods_created = pods.len();
-
-    let (ready_successes, ready_errors) = partition_results(
-        join_all(
-            pods.iter()
-                .map(|pod| client.verify_pod_condition(pod, "Ready")),
-        )
-        .await,
-    );
-    let pods_ready = ready_successes.len();
-
-    let (deletion_successes, deletion_errors) =
-        partition_results(join_all(pods.into_iter().map(|pod| client.delete(pod))).await);
-    let pods_deleted = deletion_successes.len();
-
-    let mut errors = Vec::new();
-    errors.extend(creation_errors);
-    errors.extend(ready_errors);
-    errors.extend(deletion_errors);
-
-    if let Some(error) = errors.first() {
-        panic!(
-            "Pods: {created}/{total} created, {ready}/{created} ready, {deleted}/{created} deleted; Error: {error}",
-            total = NUM_PODS,
-            created = pods_created,
-            ready = pods_ready,
-            deleted = pods_deleted,
-            error = error
-        );
+    let report = run_scale_test(
+        &client,
+        &noop_service(),
+        "agent-service-integration-test-race-condition",
+        &config,
+    )
+    .await;
+
+    complete_scale_test(&client, "start-and-stop-100-pods", &report).await
+}
+
+/// Soak test which is meant to run nightly to detect memory leaks and
+/// race conditions in the agent
+///
+/// The test is ignored by default. It can be configured with the
+/// environment variables described in [`ScaleConfig::with_env_overrides`]
+/// and started with `cargo test --test service -- --ignored`.
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn pods_should_be_started_and_stopped_reliably_over_a_long_period() -> Result<()> {
+    let client = scale_test_client().await?;
+
+    let config = ScaleConfig {
+        pods: 50,
+        concurrency: 10,
+        waves: 3,
+        churn_rate: 10.0,
+        duration: Duration::from_secs(60 * 60),
     }
+    .with_env_overrides()?;
+
+    let report = run_scale_test(
+        &client,
+        &noop_service(),
+        "agent-service-integration-test-soak",
+        &config,
+    )
+    .await;
+
+    complete_scale_test(&client, "soak", &report).await
 }
 
-fn partition_results<T, E>(results: Vec<Result<T, E>>) -> (Vec<T>, Vec<E>)
-where
-    E: fmt::Debug,
-    T: fmt::Debug,
-{
-    let (successes, errors) = results.into_iter().partition::<Vec<_>, _>(Result::is_ok);
-    let unwrapped_successes = successes.into_iter().map(Result::unwrap).collect();
-    let unwrapped_errors = errors.into_iter().map(Result::unwrap_err).collect();
+/// Prints the given scale report, publishes it as benchmark report with
+/// the given name, and returns the result of the scale test.
+///
+/// The benchmark report is published also if the scale test failed.
+/// Errors while publishing are combined into the result instead of
+/// hiding the outcome of the scale test.
+async fn complete_scale_test(client: &KubeClient, name: &str, report: &ScaleReport) -> Result<()> {
+    println!("{}", report);
+
+    let mut result = TestResult::default();
+    result.combine(&report.result());
+
+    let benchmark_report = BenchmarkReport::new(client, name).await;
+    result.combine(&benchmark_report);
+
+    if let Ok(mut benchmark_report) = benchmark_report {
+        report.add_to(&mut benchmark_report);
+        result.combine(&benchmark_report.publish().await);
+    }
 
-    (unwrapped_successes, unwrapped_errors)
+    result.into()
+}
+
+/// Creates a client with timeouts which tolerate a loaded agent.
+async fn scale_test_client() -> Result<KubeClient> {
+    let mut client = KubeClient::new().await?;
+    client.timeouts.create = Duration::from_secs(60);
+    client.timeouts.delete = Duration::from_secs(60);
+    client.timeouts.verify_status = Duration::from_secs(60);
+    Ok(client)
 }
@@ -0,0 +1,238 @@
+mod util;
+
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+
+use crate::util::fixture::{
+    close_repository, create_pod, delete_pod, start_repository, unique_name,
+};
+use crate::util::probe::{node_name, run_probe};
+use crate::util::result::TestResult;
+use crate::util::services::signal_recorder_service;
+use crate::util::status::verify_pod_condition;
+use crate::util::test_package::TestPackage;
+
+/// Tolerance in seconds for the recorded points in time
+const TOLERANCE: f64 = 2.0;
+
+/// Signals recorded by the signal-recorder-service
+#[derive(Debug)]
+struct SignalRecord {
+    /// Received signals with the points in time in seconds since epoch
+    signals: Vec<(String, f64)>,
+    /// Last heartbeat of the service in seconds since epoch
+    heartbeat: f64,
+}
+
+impl SignalRecord {
+    /// Parses the output of the probe created by [`signal_probe`].
+    fn parse(lines: &[String]) -> Result<SignalRecord> {
+        let mut signals = Vec::new();
+        let mut heartbeat = None;
+
+        for line in lines {
+            let (name, timestamp) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("Invalid line in signal record: [{}]", line))?;
+            let timestamp = timestamp
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| anyhow!("Invalid timestamp in signal record: [{}]", line))?;
+
+            match name {
+                "STARTED" => {}
+                "HEARTBEAT" => heartbeat = Some(timestamp),
+                signal => signals.push((String::from(signal), timestamp)),
+            }
+        }
+
+        Ok(SignalRecord {
+            signals,
+            heartbeat: heartbeat.ok_or_else(|| anyhow!("No heartbeat recorded"))?,
+        })
+    }
+
+    fn signal_names(&self) -> Vec<&str> {
+        self.signals.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Returns the point in time of the first SIGTERM.
+    fn first_sigterm(&self) -> Option<f64> {
+        self.signals
+            .iter()
+            .find(|(name, _)| name == "TERM")
+            .map(|(_, timestamp)| *timestamp)
+    }
+}
+
+#[tokio::test]
+async fn deletion_should_send_sigterm_and_then_sigkill_after_the_grace_period() -> Result<()> {
+    let termination_grace_period = Duration::from_secs(10);
+
+    let mut client = KubeClient::new().await?;
+    client.timeouts.delete = Duration::from_secs(60) + termination_grace_period;
+
+    let mut result = TestResult::default();
+
+    // The service ignores SIGTERM and must be killed.
+    if let Some((record, deletion_duration)) =
+        record_signals_on_deletion(&client, &mut result, termination_grace_period, None).await
+    {
+        result.check_eq("received signals", &vec!["TERM"], &record.signal_names());
+
+        if let Some(sigterm) = record.first_sigterm() {
+            let killed_after = record.heartbeat - sigterm;
+            if (killed_after - termination_grace_period.as_secs_f64()).abs() > TOLERANCE {
+                result.combine::<(), _>(&Err(format!(
+                    "The service was killed {:.1} s after SIGTERM but the termination grace \
+                    period is {} s",
+                    killed_after,
+                    termination_grace_period.as_secs()
+                )));
+            }
+        }
+
+        if deletion_duration.as_secs_f64() < termination_grace_period.as_secs_f64() - TOLERANCE {
+            result.combine::<(), _>(&Err(format!(
+                "The pod was deleted after {:?} which is less than the termination grace \
+                period of {:?}",
+                deletion_duration, termination_grace_period
+            )));
+        }
+    }
+
+    result.into()
+}
+
+#[tokio::test]
+async fn service_terminating_on_sigterm_should_not_be_killed() -> Result<()> {
+    let termination_grace_period = Duration::from_secs(30);
+
+    let mut client = KubeClient::new().await?;
+    client.timeouts.delete = Duration::from_secs(60) + termination_grace_period;
+
+    let mut result = TestResult::default();
+
+    if let Some((record, deletion_duration)) =
+        record_signals_on_deletion(&client, &mut result, termination_grace_period, Some("TERM"))
+            .await
+    {
+        result.check_eq("received signals", &vec!["TERM"], &record.signal_names());
+
+        if let Some(sigterm) = record.first_sigterm() {
+            let terminated_after = record.heartbeat - sigterm;
+            if terminated_after > TOLERANCE {
+                result.combine::<(), _>(&Err(format!(
+                    "The service was still running {:.1} s after SIGTERM although it \
+                    terminates on SIGTERM",
+                    terminated_after
+                )));
+            }
+        }
+
+        if deletion_duration >= termination_grace_period {
+            result.combine::<(), _>(&Err(format!(
+                "The deletion of the pod took {:?} although the service terminated \
+                immediately on SIGTERM",
+                deletion_duration
+            )));
+        }
+    }
+
+    result.into()
+}
+
+/// Starts the signal-recorder-service, deletes it, and returns the
+/// recorded signals together with the duration of the deletion.
+///
+/// `None` is returned if the signals could not be recorded, e.g. if the
+/// agent does not provide the logs of the probe.
+async fn record_signals_on_deletion(
+    client: &KubeClient,
+    result: &mut TestResult,
+    termination_grace_period: Duration,
+    exit_on_signal: Option<&str>,
+) -> Option<(SignalRecord, Duration)> {
+    let signal_log = format!("/tmp/{}.log", unique_name("signal-recorder-service"));
+
+    let service = signal_recorder_service();
+    let mut pod_definition = service.pod_with_env(
+        &unique_name("agent-termination-integration-test-signals"),
+        &[
+            ("SIGNAL_LOG", signal_log.as_str()),
+            ("EXIT_ON_SIGNAL", exit_on_signal.unwrap_or_default()),
+        ],
+    );
+    pod_definition
+        .spec
+        .get_or_insert_with(Default::default)
+        .termination_grace_period_seconds
+        .replace(termination_grace_period.as_secs() as i64);
+
+    let repository_result = start_repository(client, result, &[&service]).await;
+    let pod_result = create_pod(client, result, &pod_definition).await;
+
+    let mut node = None;
+
+    if let Ok(pod) = &pod_result {
+        let pod_ready = verify_pod_condition(client, pod, "Ready").await;
+        result.combine(&pod_ready);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+        node = get_status_result.ok().as_ref().and_then(node_name);
+
+        // Give the service time to install its signal handlers
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    let deletion_start = Instant::now();
+    delete_pod(client, result, pod_result).await;
+    let deletion_duration = deletion_start.elapsed();
+
+    let mut record = None;
+
+    if let Some(node) = node {
+        let probe = signal_probe(&signal_log);
+        if let Some(probe_output) = run_probe(client, result, &probe, &node).await {
+            if !probe_output.succeeded {
+                result.combine::<(), _>(&Err("The signal record could not be read"));
+            }
+            if let Some(logs) = probe_output.logs {
+                let parse_result = SignalRecord::parse(&logs);
+                result.combine(&parse_result);
+                record = parse_result.ok();
+            }
+        }
+    }
+
+    close_repository(client, result, repository_result).await;
+
+    record.map(|record| (record, deletion_duration))
+}
+
+/// Creates a job which prints and removes the signal record.
+fn signal_probe(signal_log: &str) -> TestPackage {
+    TestPackage {
+        name: unique_name("signal-probe"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: formatdoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            HEARTBEAT=$(cat "{signal_log}.heartbeat") || exit 1
+            cat "{signal_log}" || exit 1
+            echo "HEARTBEAT $HEARTBEAT"
+
+            rm -f "{signal_log}" "{signal_log}.heartbeat"
+            "#,
+            signal_log = signal_log
+        ),
+    }
+}
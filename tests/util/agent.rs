@@ -0,0 +1,279 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+
+use super::nodes::stackable_nodes;
+
+/// Label which can be set on the node to announce the agent version
+///
+/// If the label is present then it takes precedence over
+/// `nodeInfo.kubeletVersion`.
+pub const LABEL_AGENT_VERSION: &str = "stackable.tech/agent-version";
+
+/// Version of the Stackable agent
+///
+/// The version is parsed from strings like `0.6.0`, `v0.6.0` or
+/// `0.7.0-nightly`. Versions are ordered as specified by SemVer, i.e.
+/// pre-release versions are ordered before the corresponding release
+/// (`0.7.0-nightly < 0.7.0`) and the dot-separated pre-release
+/// identifiers are compared numerically if they consist of digits only
+/// (`0.7.0-rc.9 < 0.7.0-rc.10`). Numeric identifiers with leading
+/// zeros like `0.7.0-01` are rejected. Build metadata is ignored.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre_release: Option<String>,
+}
+
+#[allow(dead_code)]
+impl AgentVersion {
+    /// Detects the version of the agents on the Stackable nodes.
+    ///
+    /// If several nodes are available then the lowest version is
+    /// returned so that version-gated test cases only run if all agents
+    /// support the tested feature.
+    pub async fn detect(client: &KubeClient) -> Result<AgentVersion> {
+        let versions = stackable_nodes(client)
+            .await?
+            .iter()
+            .map(AgentVersion::from_node)
+            .collect::<Result<Vec<_>>>()?;
+
+        versions
+            .into_iter()
+            .min()
+            .ok_or_else(|| anyhow!("No Stackable node found"))
+    }
+
+    /// Reads the agent version from the given node.
+    ///
+    /// The label [`LABEL_AGENT_VERSION`] is used if present, otherwise
+    /// `nodeInfo.kubeletVersion`.
+    pub fn from_node(node: &Node) -> Result<AgentVersion> {
+        let node_name = node.metadata.name.as_deref().unwrap_or_default();
+
+        let label = node
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(LABEL_AGENT_VERSION));
+        let kubelet_version = node
+            .status
+            .as_ref()
+            .and_then(|status| status.node_info.as_ref())
+            .map(|node_info| &node_info.kubelet_version);
+
+        let version = label.or(kubelet_version).ok_or_else(|| {
+            anyhow!(
+                "Node [{}] provides neither the label [{}] nor the kubelet version",
+                node_name,
+                LABEL_AGENT_VERSION
+            )
+        })?;
+
+        version.parse::<AgentVersion>().map_err(|error| {
+            anyhow!(
+                "Agent version of node [{}] could not be parsed: {}",
+                node_name,
+                error
+            )
+        })
+    }
+
+    /// Returns true if this version satisfies the given requirement.
+    ///
+    /// The requirement consists of comma-separated comparisons which
+    /// must all hold, e.g. `>=0.5.0, <0.7.0`. The operators `=`, `>`,
+    /// `>=`, `<`, and `<=` are supported.
+    pub fn satisfies(&self, requirement: &str) -> Result<bool> {
+        let mut satisfied = true;
+
+        for comparison in requirement.split(',').map(str::trim) {
+            let (operator, version) = if let Some(version) = comparison.strip_prefix(">=") {
+                (">=", version)
+            } else if let Some(version) = comparison.strip_prefix("<=") {
+                ("<=", version)
+            } else if let Some(version) = comparison.strip_prefix('>') {
+                (">", version)
+            } else if let Some(version) = comparison.strip_prefix('<') {
+                ("<", version)
+            } else if let Some(version) = comparison.strip_prefix('=') {
+                ("=", version)
+            } else {
+                ("=", comparison)
+            };
+
+            let version = version.trim().parse::<AgentVersion>()?;
+            let ordering = self.cmp(&version);
+
+            satisfied &= match operator {
+                ">=" => ordering != Ordering::Less,
+                "<=" => ordering != Ordering::Greater,
+                ">" => ordering == Ordering::Greater,
+                "<" => ordering == Ordering::Less,
+                _ => ordering == Ordering::Equal,
+            };
+        }
+
+        Ok(satisfied)
+    }
+}
+
+impl FromStr for AgentVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(version: &str) -> Result<Self> {
+        let trimmed = version.trim();
+        let without_prefix = trimmed.strip_prefix('v').unwrap_or(trimmed);
+        let without_build_metadata = without_prefix.split('+').next().unwrap_or_default();
+
+        let (release, pre_release) = match without_build_metadata.split_once('-') {
+            Some((release, pre_release)) => (release, Some(String::from(pre_release))),
+            None => (without_build_metadata, None),
+        };
+
+        // Numeric identifiers with leading zeros are not allowed by
+        // SemVer. Rejecting them keeps the equality, which compares the
+        // identifiers as strings, consistent with the ordering.
+        let leading_zero = pre_release
+            .iter()
+            .flat_map(|pre_release| pre_release.split('.'))
+            .any(|identifier| {
+                identifier.len() > 1
+                    && identifier.starts_with('0')
+                    && identifier.bytes().all(|byte| byte.is_ascii_digit())
+            });
+        if leading_zero {
+            return Err(anyhow!(
+                "Invalid version [{}]; numeric pre-release identifiers must not contain \
+                leading zeros",
+                version
+            ));
+        }
+
+        let numbers = release
+            .split('.')
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Invalid version [{}]", version))?;
+
+        match numbers.as_slice() {
+            [major, minor, patch] => Ok(AgentVersion {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+                pre_release,
+            }),
+            _ => Err(anyhow!(
+                "Invalid version [{}]; expected [major.minor.patch]",
+                version
+            )),
+        }
+    }
+}
+
+impl Ord for AgentVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(pre_release), Some(other_pre_release)) => {
+                    compare_pre_releases(pre_release, other_pre_release)
+                }
+            })
+    }
+}
+
+impl PartialOrd for AgentVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for AgentVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre_release) = &self.pre_release {
+            write!(f, "-{}", pre_release)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two pre-release versions as specified in SemVer.
+///
+/// The dot-separated identifiers are compared from left to right.
+/// Numeric identifiers are compared numerically and have a lower
+/// precedence than alphanumeric identifiers which are compared
+/// lexically. If all identifiers are equal then the pre-release with
+/// more identifiers has the higher precedence.
+fn compare_pre_releases(pre_release: &str, other_pre_release: &str) -> Ordering {
+    let identifiers = pre_release.split('.');
+    let other_identifiers = other_pre_release.split('.');
+
+    for (identifier, other_identifier) in identifiers.clone().zip(other_identifiers.clone()) {
+        let ordering = match (
+            numeric_identifier(identifier),
+            numeric_identifier(other_identifier),
+        ) {
+            (Some(number), Some(other_number)) => number.cmp(&other_number),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => identifier.cmp(other_identifier),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    identifiers.count().cmp(&other_identifiers.count())
+}
+
+/// Returns the value of the given pre-release identifier if it is
+/// numeric.
+fn numeric_identifier(identifier: &str) -> Option<u64> {
+    if !identifier.is_empty() && identifier.bytes().all(|byte| byte.is_ascii_digit()) {
+        identifier.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Skips the current test case if the agent version does not satisfy
+/// the given requirement.
+///
+/// The macro must be used in an async test function which returns
+/// `anyhow::Result<()>`. The test case passes if it is skipped.
+///
+/// # Example
+///
+/// ```ignore
+/// #[tokio::test]
+/// async fn new_feature_should_work() -> Result<()> {
+///     let client = KubeClient::new().await?;
+///     require_agent_version!(&client, ">=0.6.0");
+///
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! require_agent_version {
+    ($client:expr, $requirement:expr) => {
+        let agent_version = $crate::util::agent::AgentVersion::detect($client).await?;
+        if !agent_version.satisfies($requirement)? {
+            println!(
+                "Test case skipped because agent version [{}] does not satisfy [{}]",
+                agent_version, $requirement
+            );
+            return Ok(());
+        }
+    };
+}
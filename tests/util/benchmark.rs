@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use serde_json::json;
+
+use super::agent::AgentVersion;
+use super::http::request_with_content_type;
+use super::scale::{env_var, Percentiles};
+
+/// Environment variable which contains the directory where the
+/// benchmark reports are written to
+pub const REPORT_DIR_ENV: &str = "AGENT_BENCHMARK_REPORT_DIR";
+
+/// Environment variable which selects the format of the written
+/// reports, either `json` (default) or `openmetrics`
+pub const REPORT_FORMAT_ENV: &str = "AGENT_BENCHMARK_REPORT_FORMAT";
+
+/// Environment variable which contains the base URI of a Prometheus
+/// pushgateway the reports are pushed to
+pub const PUSHGATEWAY_ENV: &str = "AGENT_BENCHMARK_PUSHGATEWAY";
+
+/// Job name under which the reports are pushed
+const PUSHGATEWAY_JOB: &str = "agent-integration-tests";
+
+/// Content type of the pushed reports
+///
+/// The pushgateway parses the OpenMetrics output with its parser for the
+/// Prometheus text format which ignores the `UNIT` and `EOF` lines.
+const PUSHGATEWAY_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Latency which is measured in a benchmark
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+}
+
+/// Duration from the package request until the agent closed the
+/// connection after it received the whole package
+#[allow(dead_code)]
+pub const PACKAGE_DOWNLOAD: Metric = Metric {
+    name: "agent_package_download_seconds",
+    help: "Duration from the package request until the agent closed the connection after it received the whole package",
+};
+
+/// Duration from the completed package download until the agent
+/// starts the systemd unit, i.e. mainly the unpacking of the package
+#[allow(dead_code)]
+pub const PACKAGE_UNPACK: Metric = Metric {
+    name: "agent_package_unpack_seconds",
+    help: "Duration from the completed package download until the agent starts the systemd unit",
+};
+
+/// Duration from the start of the systemd unit until it is active
+#[allow(dead_code)]
+pub const UNIT_START: Metric = Metric {
+    name: "agent_unit_start_seconds",
+    help: "Duration from the start of the systemd unit until it is active",
+};
+
+/// Duration from the creation of a pod until it is ready
+#[allow(dead_code)]
+pub const POD_READY: Metric = Metric {
+    name: "agent_pod_ready_seconds",
+    help: "Duration from the creation of a pod until it is ready",
+};
+
+/// Duration from the deletion of a pod until it is gone
+#[allow(dead_code)]
+pub const POD_DELETION: Metric = Metric {
+    name: "agent_pod_deletion_seconds",
+    help: "Duration from the deletion of a pod until it is gone",
+};
+
+/// Latencies measured in a test case together with the version of the
+/// agent
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    pub name: String,
+    pub agent_version: AgentVersion,
+    pub samples: BTreeMap<Metric, Vec<Duration>>,
+}
+
+#[allow(dead_code)]
+impl BenchmarkReport {
+    /// Creates an empty report with the given name and the detected
+    /// agent version.
+    ///
+    /// The name should identify the test case and is used as file name
+    /// and as grouping key in the pushgateway.
+    pub async fn new(client: &KubeClient, name: &str) -> Result<BenchmarkReport> {
+        Ok(BenchmarkReport {
+            name: String::from(name),
+            agent_version: AgentVersion::detect(client).await?,
+            samples: BTreeMap::new(),
+        })
+    }
+
+    /// Records a measured latency.
+    pub fn record(&mut self, metric: Metric, latency: Duration) {
+        self.samples.entry(metric).or_default().push(latency);
+    }
+
+    /// Records several measured latencies.
+    pub fn record_all(&mut self, metric: Metric, latencies: &[Duration]) {
+        self.samples
+            .entry(metric)
+            .or_default()
+            .extend_from_slice(latencies);
+    }
+
+    /// Returns the report as JSON document.
+    pub fn to_json(&self) -> String {
+        let metrics = self
+            .samples
+            .iter()
+            .map(|(metric, latencies)| {
+                let percentiles = Percentiles::of(latencies);
+                let value = json!({
+                    "help": metric.help,
+                    "count": latencies.len(),
+                    "sum": sum(latencies).as_secs_f64(),
+                    "p50": percentiles.as_ref().map(|p| p.p50.as_secs_f64()),
+                    "p90": percentiles.as_ref().map(|p| p.p90.as_secs_f64()),
+                    "p99": percentiles.as_ref().map(|p| p.p99.as_secs_f64()),
+                    "max": percentiles.as_ref().map(|p| p.max.as_secs_f64()),
+                    "samples": latencies
+                        .iter()
+                        .map(Duration::as_secs_f64)
+                        .collect::<Vec<_>>(),
+                });
+                (metric.name, value)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let report = json!({
+            "name": self.name,
+            "agentVersion": self.agent_version.to_string(),
+            "metrics": metrics,
+        });
+
+        serde_json::to_string_pretty(&report).unwrap()
+    }
+
+    /// Returns the report as summaries in the OpenMetrics text format.
+    ///
+    /// The output is also accepted by a Prometheus pushgateway.
+    pub fn to_openmetrics(&self) -> String {
+        let labels = format!(
+            "test=\"{}\",agent_version=\"{}\"",
+            escape_label_value(&self.name),
+            escape_label_value(&self.agent_version.to_string())
+        );
+
+        let mut output = String::new();
+
+        for (metric, latencies) in self.samples.iter() {
+            let name = metric.name;
+            writeln!(output, "# TYPE {} summary", name).unwrap();
+            writeln!(output, "# UNIT {} seconds", name).unwrap();
+            writeln!(output, "# HELP {} {}", name, metric.help).unwrap();
+
+            if let Some(percentiles) = Percentiles::of(latencies) {
+                for (quantile, value) in [
+                    ("0.5", percentiles.p50),
+                    ("0.9", percentiles.p90),
+                    ("0.99", percentiles.p99),
+                    ("1.0", percentiles.max),
+                ]
+                .iter()
+                {
+                    writeln!(
+                        output,
+                        "{}{{{},quantile=\"{}\"}} {}",
+                        name,
+                        labels,
+                        quantile,
+                        value.as_secs_f64()
+                    )
+                    .unwrap();
+                }
+            }
+
+            writeln!(
+                output,
+                "{}_sum{{{}}} {}",
+                name,
+                labels,
+                sum(latencies).as_secs_f64()
+            )
+            .unwrap();
+            writeln!(output, "{}_count{{{}}} {}", name, labels, latencies.len()).unwrap();
+        }
+
+        output.push_str("# EOF\n");
+        output
+    }
+
+    /// Writes the report to the directory given in [`REPORT_DIR_ENV`]
+    /// and pushes it to the pushgateway given in [`PUSHGATEWAY_ENV`].
+    ///
+    /// Nothing is done if the environment variables are not set.
+    pub async fn publish(&self) -> Result<()> {
+        if let Some(report_dir) = env_var::<String>(REPORT_DIR_ENV)? {
+            let format = env_var::<String>(REPORT_FORMAT_ENV)?;
+            let (extension, content) = match format.as_deref() {
+                None | Some("json") => ("json", self.to_json()),
+                Some("openmetrics") => ("txt", self.to_openmetrics()),
+                Some(other) => {
+                    return Err(anyhow!(
+                        "The benchmark report format [{}] is not supported",
+                        other
+                    ))
+                }
+            };
+
+            fs::create_dir_all(&report_dir)?;
+            let path = Path::new(&report_dir).join(format!("{}.{}", self.name, extension));
+            fs::write(&path, content).map_err(|error| {
+                anyhow!(
+                    "The benchmark report could not be written to [{}]: {}",
+                    path.display(),
+                    error
+                )
+            })?;
+        }
+
+        if let Some(pushgateway) = env_var::<String>(PUSHGATEWAY_ENV)? {
+            let uri = format!(
+                "{}/metrics/job/{}/test/{}",
+                pushgateway.trim_end_matches('/'),
+                PUSHGATEWAY_JOB,
+                self.name
+            );
+            let response = request_with_content_type(
+                "PUT",
+                &uri,
+                &self.to_openmetrics(),
+                Some(PUSHGATEWAY_CONTENT_TYPE),
+            )
+            .await?;
+            if !(200..300).contains(&response.status) {
+                return Err(anyhow!(
+                    "The benchmark report could not be pushed to [{}]: {} {}",
+                    uri,
+                    response.status,
+                    response.body
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn sum(latencies: &[Duration]) -> Duration {
+    latencies.iter().sum()
+}
+
+/// Escapes a label value as required by the OpenMetrics text format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
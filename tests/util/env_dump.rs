@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use serde::Deserialize;
+
+use super::probe::run_job;
+use super::result::TestResult;
+use super::services::env_dump_job;
+
+/// Line which precedes the JSON document of the env-dump-job
+const BEGIN_MARKER: &str = "ENV_DUMP_BEGIN";
+
+/// Line which follows the JSON document of the env-dump-job
+const END_MARKER: &str = "ENV_DUMP_END";
+
+/// Environment and kubeconfig state printed by the env-dump-job
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvDump {
+    pub environment: BTreeMap<String, String>,
+    /// Keys which occur in the kubeconfig; the values are not printed
+    pub kubeconfig_keys: Option<Vec<String>>,
+    pub kubeconfig_error: Option<String>,
+    /// Files referenced in the kubeconfig
+    pub kubeconfig_files: BTreeMap<String, KubeconfigFile>,
+    /// Outcome of the access review sent with the kubeconfig
+    pub kubeconfig_access_review: Option<AccessReview>,
+}
+
+/// File referenced in the kubeconfig
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+pub struct KubeconfigFile {
+    pub readable: bool,
+    pub sha256: Option<String>,
+}
+
+/// Outcome of a SelfSubjectAccessReview sent by the env-dump-job with
+/// the credentials from the kubeconfig
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+pub struct AccessReview {
+    /// HTTP status of the response or `None` if no response was received
+    pub status: Option<u16>,
+    /// Whether the reviewed access is allowed
+    pub allowed: Option<bool>,
+    /// Reason why the review failed
+    pub error: Option<String>,
+}
+
+#[allow(dead_code)]
+impl EnvDump {
+    /// Parses the output of the env-dump-job.
+    ///
+    /// Journald splits long lines into several entries, therefore all
+    /// lines between the markers are concatenated before parsing. This
+    /// is lossless because the JSON document does not contain line
+    /// breaks.
+    pub fn from_logs(lines: &[String]) -> Result<EnvDump> {
+        let document = lines
+            .iter()
+            .skip_while(|line| line.as_str() != BEGIN_MARKER)
+            .skip(1)
+            .take_while(|line| line.as_str() != END_MARKER)
+            .map(String::as_str)
+            .collect::<String>();
+
+        if document.is_empty() {
+            return Err(anyhow!(
+                "The logs do not contain an environment dump: {:?}",
+                lines
+            ));
+        }
+
+        serde_json::from_str(&document)
+            .map_err(|error| anyhow!("The environment dump could not be parsed: {}", error))
+    }
+}
+
+/// Runs the env-dump-job in the given pod and returns the parsed
+/// environment dump together with the state of the terminated pod.
+///
+/// The pod definition should be created with
+/// [`super::test_package::TestPackage::pod`] on [`env_dump_job`] and can
+/// be extended with environment variables and further settings.
+///
+/// `None` is returned if the job could not be run or if the agent does
+/// not provide logs. In the first case the error is applied on
+/// `result`.
+#[allow(dead_code)]
+pub async fn run_env_dump(
+    client: &KubeClient,
+    result: &mut TestResult,
+    pod_definition: &Pod,
+) -> Option<(EnvDump, Pod)> {
+    let job = env_dump_job();
+    let job_output = run_job(client, result, &job, pod_definition).await?;
+
+    if !job_output.succeeded {
+        result.combine::<(), _>(&Err(format!(
+            "The env-dump-job failed: {:?}",
+            job_output.logs
+        )));
+    }
+
+    let parse_result = EnvDump::from_logs(&job_output.logs?);
+    result.combine(&parse_result);
+
+    Some((parse_result.ok()?, job_output.pod))
+}
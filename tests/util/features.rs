@@ -0,0 +1,41 @@
+use integration_test_commons::test::prelude::*;
+
+use super::result::TestResult;
+
+/// Pod annotation which states if the agent provides the container logs
+#[allow(dead_code)]
+pub const LOGS: &str = "featureLogs";
+
+/// Pod annotation which states if the agent counts container restarts
+#[allow(dead_code)]
+pub const RESTART_COUNT: &str = "featureRestartCount";
+
+/// Returns true if the agent announces the given feature on the pod.
+///
+/// Features are announced with annotations containing either `true` or
+/// `false`. An unknown value or a missing annotation is applied as
+/// error on `result`.
+#[allow(dead_code)]
+pub async fn feature_enabled(
+    client: &KubeClient,
+    result: &mut TestResult,
+    pod: &Pod,
+    annotation_key: &str,
+) -> bool {
+    let get_annotation_result = client.get_annotation::<Pod>(pod, annotation_key).await;
+    result.combine(&get_annotation_result);
+
+    match get_annotation_result.as_deref() {
+        Ok("true") => true,
+        Ok("false") => false,
+        Ok(value) => {
+            result.combine::<(), _>(&Err(format!(
+                "Pod annotation [{}] contains unknown value [{}]; \
+                expected [true] or [false]",
+                annotation_key, value,
+            )));
+            false
+        }
+        _ => false,
+    }
+}
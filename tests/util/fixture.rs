@@ -0,0 +1,124 @@
+use anyhow::Result;
+use integration_test_commons::test::prelude::*;
+use uuid::Uuid;
+
+use super::repository::{StackableRepositoryBuilder, StackableRepositoryInstance};
+use super::result::TestResult;
+use super::test_package::TestPackage;
+
+/// Appends a random suffix to the given name.
+///
+/// Resources with unique names do not interfere with each other, so
+/// test cases using them can run concurrently.
+#[allow(dead_code)]
+pub fn unique_name(name: &str) -> String {
+    format!("{}-{}", name, Uuid::new_v4())
+}
+
+/// Starts a uniquely named repository providing the given packages and
+/// creates the given pod.
+///
+/// Errors are applied on `result`. The returned resources must be
+/// released with [`tear_down`].
+#[allow(dead_code)]
+pub async fn set_up(
+    client: &KubeClient,
+    result: &mut TestResult,
+    packages: &[&TestPackage],
+    pod_definition: &Pod,
+) -> (Result<StackableRepositoryInstance>, Result<Pod>) {
+    let repository_result = start_repository(client, result, packages).await;
+    let pod_result = create_pod(client, result, pod_definition).await;
+
+    (repository_result, pod_result)
+}
+
+/// Deletes the pod and closes the repository which were created by
+/// [`set_up`].
+#[allow(dead_code)]
+pub async fn tear_down(
+    client: &KubeClient,
+    result: &mut TestResult,
+    repository_result: Result<StackableRepositoryInstance>,
+    pod_result: Result<Pod>,
+) {
+    delete_pod(client, result, pod_result).await;
+    close_repository(client, result, repository_result).await;
+}
+
+/// Starts a uniquely named repository providing the given packages.
+#[allow(dead_code)]
+pub async fn start_repository(
+    client: &KubeClient,
+    result: &mut TestResult,
+    packages: &[&TestPackage],
+) -> Result<StackableRepositoryInstance> {
+    run_repository(client, result, packages, false).await
+}
+
+/// Starts a uniquely named repository providing the given packages
+/// which records the package downloads.
+#[allow(dead_code)]
+pub async fn start_recording_repository(
+    client: &KubeClient,
+    result: &mut TestResult,
+    packages: &[&TestPackage],
+) -> Result<StackableRepositoryInstance> {
+    run_repository(client, result, packages, true).await
+}
+
+async fn run_repository(
+    client: &KubeClient,
+    result: &mut TestResult,
+    packages: &[&TestPackage],
+    record_downloads: bool,
+) -> Result<StackableRepositoryInstance> {
+    let mut repository_builder =
+        StackableRepositoryBuilder::new(&unique_name("agent-integration-test-repository"));
+    for package in packages {
+        repository_builder.package(package);
+    }
+    if record_downloads {
+        repository_builder.record_downloads();
+    }
+
+    let repository_result = repository_builder.run(client).await;
+    result.combine(&repository_result);
+    repository_result
+}
+
+/// Creates the given pod.
+#[allow(dead_code)]
+pub async fn create_pod(
+    client: &KubeClient,
+    result: &mut TestResult,
+    pod_definition: &Pod,
+) -> Result<Pod> {
+    let pod_result = client
+        .create::<Pod>(&serde_yaml::to_string(pod_definition).unwrap())
+        .await;
+    result.combine(&pod_result);
+    pod_result
+}
+
+/// Deletes the pod if it was successfully created.
+#[allow(dead_code)]
+pub async fn delete_pod(client: &KubeClient, result: &mut TestResult, pod_result: Result<Pod>) {
+    if let Ok(pod) = pod_result {
+        let deletion_result = client.delete(pod).await;
+        result.combine(&deletion_result);
+    }
+}
+
+/// Closes the repository if it was successfully started.
+#[allow(dead_code)]
+pub async fn close_repository(
+    client: &KubeClient,
+    result: &mut TestResult,
+    repository_result: Result<StackableRepositoryInstance>,
+) {
+    if let Ok(repository) = repository_result {
+        let close_result = repository.close(client).await;
+        result.combine(&close_result);
+    }
+}
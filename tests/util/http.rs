@@ -0,0 +1,106 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use http::header::CONTENT_TYPE;
+use hyper::{Body, Client, Request};
+use uuid::Uuid;
+
+/// Timeout for a request including the reception of the response body
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Response of an HTTP request
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Sends a GET request to the given URI.
+#[allow(dead_code)]
+pub async fn get(uri: &str) -> Result<HttpResponse> {
+    request("GET", uri, "").await
+}
+
+/// Sends GET requests to the given URI until a response is received or
+/// the timeout is reached.
+///
+/// This is useful if a service is already reported as ready but does
+/// not yet accept connections.
+#[allow(dead_code)]
+pub async fn get_eventually(uri: &str, timeout: Duration) -> Result<HttpResponse> {
+    let start = Instant::now();
+
+    loop {
+        match get(uri).await {
+            Ok(response) => return Ok(response),
+            Err(error) if start.elapsed() >= timeout => {
+                return Err(anyhow!(
+                    "[{}] could not be requested within {:?}: {}",
+                    uri,
+                    timeout,
+                    error
+                ))
+            }
+            Err(_) => tokio::time::sleep(Duration::from_secs(1)).await,
+        }
+    }
+}
+
+/// Sends a request with the given method and body to the given URI and
+/// returns the response.
+#[allow(dead_code)]
+pub async fn request(method: &str, uri: &str, body: &str) -> Result<HttpResponse> {
+    request_with_content_type(method, uri, body, None).await
+}
+
+/// Sends a request with the given method, body, and content type to the
+/// given URI and returns the response.
+///
+/// An error is returned if no response is received within [`TIMEOUT`].
+#[allow(dead_code)]
+pub async fn request_with_content_type(
+    method: &str,
+    uri: &str,
+    body: &str,
+    content_type: Option<&str>,
+) -> Result<HttpResponse> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(content_type) = content_type {
+        request = request.header(CONTENT_TYPE, content_type);
+    }
+    let request = request.body(Body::from(body.to_owned()))?;
+
+    let response = tokio::time::timeout(TIMEOUT, async {
+        let response = Client::new().request(request).await?;
+        let status = response.status().as_u16();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok::<_, hyper::Error>(HttpResponse {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    })
+    .await
+    .map_err(|_| anyhow!("[{}] did not respond within {:?}", uri, TIMEOUT))??;
+
+    Ok(response)
+}
+
+/// Returns a random port in the range from 30000 to 39999.
+///
+/// Test services listening on the host network should use random
+/// ports so that concurrently running test cases do not collide.
+#[allow(dead_code)]
+pub fn random_port() -> u16 {
+    30000 + (Uuid::new_v4().as_u128() % 10000) as u16
+}
+
+/// Formats the given IP address and port as socket address.
+#[allow(dead_code)]
+pub fn format_address(ip: &str, port: u16) -> Result<String> {
+    let ip = ip
+        .parse::<IpAddr>()
+        .map_err(|error| anyhow!("[{}] is not a valid IP address: {}", ip, error))?;
+    Ok(SocketAddr::new(ip, port).to_string())
+}
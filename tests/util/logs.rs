@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use integration_test_commons::test::prelude::*;
+use kube::api::Api;
+use kube::Client;
+
+/// Follows the logs of a pod line by line
+///
+/// The logs are streamed directly with a [`kube::Client`] because the
+/// [`KubeClient`] only retrieves complete logs.
+#[allow(dead_code)]
+pub struct LogFollower {
+    stream: BoxStream<'static, kube::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    lines: VecDeque<String>,
+}
+
+#[allow(dead_code)]
+impl LogFollower {
+    /// Starts following the logs of the given pod.
+    ///
+    /// `follow` is set in the given parameters; all other parameters
+    /// are passed unchanged.
+    pub async fn start(pod: &Pod, params: &LogParams) -> Result<LogFollower> {
+        let params = LogParams {
+            follow: true,
+            ..params.to_owned()
+        };
+        let stream = log_stream(pod, &params).await?;
+
+        Ok(LogFollower {
+            stream,
+            buffer: Vec::new(),
+            lines: VecDeque::new(),
+        })
+    }
+
+    /// Returns the next line or `None` if the stream ended.
+    ///
+    /// An error is returned if neither a line nor the end of the stream
+    /// was received within the given timeout.
+    pub async fn next_line(&mut self, timeout: Duration) -> Result<Option<String>> {
+        tokio::time::timeout(timeout, self.read_line())
+            .await
+            .map_err(|_| anyhow!("No log line was received within {:?}", timeout))?
+    }
+
+    /// Reads until the end of the stream and returns the remaining
+    /// lines.
+    ///
+    /// An error is returned if the stream did not end within the given
+    /// timeout.
+    pub async fn read_to_end(&mut self, timeout: Duration) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        tokio::time::timeout(timeout, async {
+            while let Some(line) = self.read_line().await? {
+                lines.push(line);
+            }
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "The log stream did not end within {:?}; lines received in the meantime: {:?}",
+                timeout,
+                lines
+            )
+        })??;
+
+        Ok(lines)
+    }
+
+    /// Reads the next line from the stream.
+    ///
+    /// Cancelling this future does not lose data because received
+    /// chunks are stored in the buffer before lines are extracted.
+    async fn read_line(&mut self) -> Result<Option<String>> {
+        loop {
+            if let Some(line) = self.lines.pop_front() {
+                return Ok(Some(line));
+            }
+
+            match self.stream.next().await {
+                Some(chunk) => {
+                    self.buffer.extend_from_slice(&chunk?);
+                    self.split_lines();
+                }
+                None if self.buffer.is_empty() => return Ok(None),
+                None => {
+                    let rest = self.buffer.split_off(0);
+                    return Ok(Some(String::from_utf8_lossy(&rest).into_owned()));
+                }
+            }
+        }
+    }
+
+    /// Moves all complete lines from the buffer to the line queue.
+    fn split_lines(&mut self) {
+        while let Some(position) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=position).collect::<Vec<_>>();
+            self.lines
+                .push_back(String::from_utf8_lossy(&line[..position]).into_owned());
+        }
+    }
+}
+
+/// Retrieves the logs of the given pod as raw bytes.
+///
+/// In contrast to [`KubeClient::get_logs`] the logs are neither split
+/// into lines nor decoded, so truncated logs can be inspected.
+#[allow(dead_code)]
+pub async fn raw_logs(pod: &Pod, params: &LogParams) -> Result<Vec<u8>> {
+    let chunks = log_stream(pod, params)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(chunks.concat())
+}
+
+/// Opens a stream on the logs of the given pod.
+async fn log_stream(
+    pod: &Pod,
+    params: &LogParams,
+) -> Result<BoxStream<'static, kube::Result<Vec<u8>>>> {
+    let name = pod
+        .metadata
+        .name
+        .as_deref()
+        .ok_or_else(|| anyhow!("The pod has no name"))?;
+    let namespace = pod.metadata.namespace.as_deref().unwrap_or("default");
+
+    let api: Api<Pod> = Api::namespaced(Client::try_default().await?, namespace);
+
+    Ok(api
+        .log_stream(name, params)
+        .await?
+        .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+        .boxed())
+}
@@ -1,4 +1,16 @@
+pub mod agent;
+pub mod benchmark;
+pub mod env_dump;
+pub mod features;
+pub mod fixture;
+pub mod http;
+pub mod logs;
+pub mod nodes;
+pub mod probe;
 pub mod repository;
 pub mod result;
+pub mod scale;
 pub mod services;
+pub mod status;
 pub mod test_package;
+pub mod units;
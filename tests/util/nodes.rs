@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::core::v1::{
+    Affinity, NodeAffinity, NodeSelector, NodeSelectorRequirement, NodeSelectorTerm,
+};
+use kube::api::{Api, ListParams};
+use kube::Client;
+
+/// Label selector for the nodes managed by the Stackable agent
+#[allow(dead_code)]
+pub const STACKABLE_NODE_SELECTOR: &str = "kubernetes.io/arch=stackable-linux";
+
+/// Returns all nodes managed by the Stackable agent.
+///
+/// An error is returned if there is no such node.
+#[allow(dead_code)]
+pub async fn stackable_nodes(client: &KubeClient) -> Result<Vec<Node>> {
+    let nodes = client
+        .list_labeled::<Node>(STACKABLE_NODE_SELECTOR)
+        .await?
+        .items;
+
+    if nodes.is_empty() {
+        Err(anyhow!("No Stackable node found"))
+    } else {
+        Ok(nodes)
+    }
+}
+
+/// Returns the first node managed by the Stackable agent.
+#[allow(dead_code)]
+pub async fn stackable_node(client: &KubeClient) -> Result<Node> {
+    let mut nodes = stackable_nodes(client).await?;
+    Ok(nodes.remove(0))
+}
+
+/// Returns the names of all nodes managed by the Stackable agent.
+#[allow(dead_code)]
+pub async fn stackable_node_names(client: &KubeClient) -> Result<Vec<String>> {
+    Ok(stackable_nodes(client)
+        .await?
+        .into_iter()
+        .filter_map(|node| node.metadata.name)
+        .collect())
+}
+
+/// Returns the pods in all namespaces which are bound to the given node
+/// and not terminated.
+#[allow(dead_code)]
+pub async fn active_pods_on_node(node_name: &str) -> Result<Vec<Pod>> {
+    let api: Api<Pod> = Api::all(Client::try_default().await?);
+    let params = ListParams::default().fields(&format!("spec.nodeName={}", node_name));
+
+    let pods = api
+        .list(&params)
+        .await?
+        .items
+        .into_iter()
+        .filter(|pod| {
+            let phase = pod
+                .status
+                .as_ref()
+                .and_then(|status| status.phase.as_deref());
+            phase != Some("Succeeded") && phase != Some("Failed")
+        })
+        .collect();
+
+    Ok(pods)
+}
+
+/// Returns the number of pods which can still be scheduled on the given
+/// node.
+///
+/// The allocatable pods of a node do not account for the pods which
+/// are already running on it, so the active pods are subtracted.
+#[allow(dead_code)]
+pub async fn free_pod_slots(node: &Node) -> Result<u32> {
+    let node_name = node
+        .metadata
+        .name
+        .as_deref()
+        .ok_or_else(|| anyhow!("The node has no name"))?;
+
+    let allocatable_pods = get_allocatable_pods(node);
+    let active_pods = active_pods_on_node(node_name).await?.len() as u32;
+
+    Ok(allocatable_pods.saturating_sub(active_pods))
+}
+
+/// Restricts the given pod to the given node with a node affinity.
+///
+/// In contrast to setting `nodeName`, the pod still passes the
+/// scheduler, so the resources of the node are taken into account.
+#[allow(dead_code)]
+pub fn require_node(pod: &mut Pod, node_name: &str) {
+    let spec = pod.spec.get_or_insert_with(Default::default);
+    spec.affinity = Some(Affinity {
+        node_affinity: Some(NodeAffinity {
+            required_during_scheduling_ignored_during_execution: Some(NodeSelector {
+                node_selector_terms: vec![NodeSelectorTerm {
+                    match_fields: Some(vec![NodeSelectorRequirement {
+                        key: String::from("metadata.name"),
+                        operator: String::from("In"),
+                        values: Some(vec![String::from(node_name)]),
+                    }]),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+}
@@ -0,0 +1,117 @@
+use integration_test_commons::test::prelude::*;
+
+use super::features::{feature_enabled, LOGS};
+use super::fixture::{set_up, tear_down, unique_name};
+use super::result::TestResult;
+use super::status::verify_status;
+use super::test_package::TestPackage;
+
+/// Outcome of a job
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct JobOutput {
+    /// True if the job terminated successfully
+    pub succeeded: bool,
+    /// Output of the job or `None` if the agent does not provide logs
+    pub logs: Option<Vec<String>>,
+    /// State of the terminated pod
+    pub pod: Pod,
+}
+
+/// Runs the given job on the given node and waits until it terminated.
+///
+/// Probe jobs inspect the node from the inside, e.g. files written by
+/// a service or the process table, also after the inspected pod was
+/// deleted. `None` is returned if the job could not be run; the error
+/// is applied on `result` in this case.
+#[allow(dead_code)]
+pub async fn run_probe(
+    client: &KubeClient,
+    result: &mut TestResult,
+    job: &TestPackage,
+    node_name: &str,
+) -> Option<JobOutput> {
+    run_probe_with_env(client, result, job, node_name, &[]).await
+}
+
+/// Runs the given job with the given environment variables on the
+/// given node and waits until it terminated.
+///
+/// See [`run_probe`].
+#[allow(dead_code)]
+pub async fn run_probe_with_env(
+    client: &KubeClient,
+    result: &mut TestResult,
+    job: &TestPackage,
+    node_name: &str,
+    env: &[(&str, &str)],
+) -> Option<JobOutput> {
+    let mut pod_definition = job.pod_with_env(&unique_name("agent-integration-test-probe"), env);
+    let spec = pod_definition.spec.get_or_insert_with(Default::default);
+    spec.node_name.replace(String::from(node_name));
+    spec.restart_policy.replace(String::from("Never"));
+
+    run_job(client, result, job, &pod_definition).await
+}
+
+/// Runs the given pod which starts the given job, waits until it
+/// terminated, and collects its output.
+///
+/// The pod and the repository providing the job are removed
+/// afterwards. `None` is returned if the job could not be run; the
+/// error is applied on `result` in this case.
+#[allow(dead_code)]
+pub async fn run_job(
+    client: &KubeClient,
+    result: &mut TestResult,
+    job: &TestPackage,
+    pod_definition: &Pod,
+) -> Option<JobOutput> {
+    let (repository_result, pod_result) = set_up(client, result, &[job], pod_definition).await;
+
+    let mut job_output = None;
+
+    if let Ok(pod) = &pod_result {
+        let verify_status_result = verify_status(client, pod, |pod| {
+            let phase = pod.status.as_ref().and_then(|status| status.phase.as_ref());
+            phase == Some(&String::from("Succeeded")) || phase == Some(&String::from("Failed"))
+        })
+        .await;
+        result.combine(&verify_status_result);
+
+        let get_status_result = client.get_status(pod).await;
+        result.combine(&get_status_result);
+
+        if let (Ok(_), Ok(pod)) = (verify_status_result, get_status_result) {
+            let succeeded = pod
+                .status
+                .as_ref()
+                .and_then(|status| status.phase.as_deref())
+                == Some("Succeeded");
+
+            let logs = if feature_enabled(client, result, &pod, LOGS).await {
+                let logs_result = client.get_logs(&pod, &LogParams::default()).await;
+                result.combine(&logs_result);
+                logs_result.ok()
+            } else {
+                None
+            };
+
+            job_output = Some(JobOutput {
+                succeeded,
+                logs,
+                pod,
+            });
+        }
+    }
+
+    tear_down(client, result, repository_result, pod_result).await;
+
+    job_output
+}
+
+/// Returns the name of the node where the given pod is scheduled.
+#[allow(dead_code)]
+pub fn node_name(pod: &Pod) -> Option<String> {
+    pod.spec.as_ref().and_then(|spec| spec.node_name.to_owned())
+}
@@ -1,10 +1,22 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::io;
 use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, net::SocketAddr};
 
 use anyhow::anyhow;
 use anyhow::Result;
-use http::header::CONTENT_TYPE;
-use http::{HeaderValue, Response, Uri};
+use futures::ready;
+use futures::stream::{self, StreamExt};
+use http::header::{CONNECTION, CONTENT_TYPE};
+use http::{HeaderValue, Request, Response, Uri};
+use hyper::server::accept;
+use hyper::service::{make_service_fn, service_fn, Service};
+use hyper::Server;
 use integration_test_commons::test::kube::KubeClient;
 use kube::CustomResource;
 use nix::ifaddrs;
@@ -13,12 +25,32 @@ use nix::sys::socket::SockAddr;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::oneshot::{self, Sender};
+use tokio::time::Sleep;
 use warp::hyper::Body;
 use warp::{path::FullPath, Filter};
 
 use super::test_package::TestPackage;
 
+/// Size of the chunks in which the packages are sent
+///
+/// The packages are streamed so that it is known when the whole package
+/// was handed over to the connection.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Maximum time to wait for the client to close the connection after a
+/// package was sent
+///
+/// The download is not recorded as completed if the client does not
+/// close the connection in time.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time to wait before accepting further connections after an accept
+/// error, e.g. if the process ran out of file descriptors
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
+
 /// Specification of a Stackable repository
 #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 #[kube(
@@ -81,12 +113,134 @@ impl From<&[TestPackage]> for StackableRepositoryMetadata {
     }
 }
 
+/// Download of a package from the repository server
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct PackageDownload {
+    /// Repository path of the package
+    pub path: String,
+    /// Time when the request was received
+    pub start: Instant,
+    /// Time when the client closed the connection after the whole
+    /// package was sent or `None` if the download is not completed
+    ///
+    /// The package response requests the client to close the connection,
+    /// so the end is only recorded after the client received the whole
+    /// package and not already when the package was handed over to the
+    /// socket buffer.
+    pub end: Option<Instant>,
+}
+
+/// Index of the package download whose body was completely handed over
+/// to a connection
+///
+/// The slot is shared between a connection and the requests served on
+/// it.
+#[derive(Clone, Debug, Default)]
+struct CompletedDownload(Arc<Mutex<Option<usize>>>);
+
+/// Connection to the repository server which records the end of a
+/// package download when the client closes the connection
+struct RecordedConnection {
+    stream: TcpStream,
+    downloads: Arc<Mutex<Vec<PackageDownload>>>,
+    completed_download: CompletedDownload,
+    write_closed: bool,
+    close_deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl RecordedConnection {
+    fn new(stream: TcpStream, downloads: Arc<Mutex<Vec<PackageDownload>>>) -> Self {
+        RecordedConnection {
+            stream,
+            downloads,
+            completed_download: CompletedDownload::default(),
+            write_closed: false,
+            close_deadline: None,
+        }
+    }
+
+    /// Records the end of the package download which was completely
+    /// sent on this connection.
+    fn record_closed_by_client(&self) {
+        if let Some(index) = self.completed_download.0.lock().unwrap().take() {
+            if let Some(download) = self.downloads.lock().unwrap().get_mut(index) {
+                download.end = Some(Instant::now());
+            }
+        }
+    }
+}
+
+impl AsyncRead for RecordedConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.stream).poll_read(cx, buf))?;
+        if buf.remaining() > 0 && buf.filled().len() == filled {
+            this.record_closed_by_client();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for RecordedConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+
+    /// Closes the write side of the connection and waits until the
+    /// client closes the connection, i.e. until the client received the
+    /// whole response, or until [`CLOSE_TIMEOUT`] is reached.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        if !this.write_closed {
+            ready!(Pin::new(&mut this.stream).poll_shutdown(cx))?;
+            this.write_closed = true;
+        }
+
+        let close_deadline = this
+            .close_deadline
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(CLOSE_TIMEOUT)));
+
+        loop {
+            if close_deadline.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut buffer = [0; 1024];
+            let mut read_buf = ReadBuf::new(&mut buffer);
+            match ready!(Pin::new(&mut this.stream).poll_read(cx, &mut read_buf)) {
+                Ok(()) if !read_buf.filled().is_empty() => continue,
+                Ok(()) => {
+                    this.record_closed_by_client();
+                    return Poll::Ready(Ok(()));
+                }
+                Err(_) => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
 /// Builder for a Stackable repository with test packages
 pub struct StackableRepositoryBuilder {
     name: String,
     repo_type: String,
     packages: Vec<TestPackage>,
     serve: bool,
+    record_downloads: bool,
     uri: Option<String>,
 }
 
@@ -98,6 +252,7 @@ impl StackableRepositoryBuilder {
             repo_type: String::from("StackableRepo"),
             packages: Vec::new(),
             serve: true,
+            record_downloads: false,
             uri: None,
         }
     }
@@ -116,6 +271,18 @@ impl StackableRepositoryBuilder {
         self
     }
 
+    /// Records the package downloads.
+    ///
+    /// The package responses request the client to close the connection,
+    /// so that the end of a download can be recorded when the client
+    /// closed it. The recorded downloads are returned by
+    /// [`StackableRepositoryInstance::downloads`].
+    #[allow(dead_code)]
+    pub fn record_downloads(&mut self) -> &Self {
+        self.record_downloads = true;
+        self
+    }
+
     /// Sets an URI.
     ///
     /// A web server serving the given packages will not be started.
@@ -133,8 +300,14 @@ impl StackableRepositoryBuilder {
     ///
     /// [`StackableRepositoryInstance::close`] must be called to stop and clean up this instance.
     pub async fn run(&self, client: &KubeClient) -> Result<StackableRepositoryInstance> {
+        let downloads = Arc::new(Mutex::new(Vec::new()));
+
         let (uri, shutdown_sender) = if self.serve {
-            let (address, shutdown_sender) = serve(&self.packages)?;
+            let (address, shutdown_sender) = if self.record_downloads {
+                serve_recording_downloads(&self.packages, downloads.clone())?
+            } else {
+                serve(&self.packages)?
+            };
             let uri = Uri::builder()
                 .scheme("http")
                 .authority(address.to_string().as_str())
@@ -153,6 +326,7 @@ impl StackableRepositoryBuilder {
                     name: self.name.to_owned(),
                     repository,
                     shutdown_sender,
+                    downloads,
                 };
                 Ok(instance)
             }
@@ -171,9 +345,20 @@ pub struct StackableRepositoryInstance {
     name: String,
     repository: Repository,
     shutdown_sender: Option<Sender<()>>,
+    downloads: Arc<Mutex<Vec<PackageDownload>>>,
 }
 
 impl StackableRepositoryInstance {
+    /// Returns the package downloads served so far in the order of
+    /// their requests.
+    ///
+    /// The downloads are only recorded if the repository was built with
+    /// [`StackableRepositoryBuilder::record_downloads`].
+    #[allow(dead_code)]
+    pub fn downloads(&self) -> Vec<PackageDownload> {
+        self.downloads.lock().unwrap().to_owned()
+    }
+
     /// Closes the Stackable repository instance
     ///
     /// The repository is deleted on the Kubernetes API server and the web server is shut down.
@@ -211,19 +396,12 @@ fn serve(packages: &[TestPackage]) -> Result<(SocketAddr, Sender<()>)> {
     let ip_address = default_ip_address()?;
     let socket_address = SocketAddr::new(ip_address, 0);
 
-    let packages_cloned = packages.to_owned();
-    let metadata_route = warp::path("metadata.json").map(move || {
-        warp::reply::json(&StackableRepositoryMetadata::from(packages_cloned.as_ref()))
-    });
-
     let packages_cloned = packages.to_owned();
     let package_route = warp::path::full().and_then(move |path: FullPath| {
         let packages = packages_cloned.to_owned();
 
         async move {
-            packages
-                .iter()
-                .find(|package| format!("/{}", package.repository_path()) == path.as_str())
+            find_package(&packages, &path)
                 .map(|package| {
                     Response::builder()
                         .header(CONTENT_TYPE, HeaderValue::from_static("application/gzip"))
@@ -234,7 +412,7 @@ fn serve(packages: &[TestPackage]) -> Result<(SocketAddr, Sender<()>)> {
         }
     });
 
-    let routes = metadata_route.or(package_route);
+    let routes = metadata_route(packages).or(package_route);
 
     let (tx, rx) = oneshot::channel::<()>();
 
@@ -248,6 +426,147 @@ fn serve(packages: &[TestPackage]) -> Result<(SocketAddr, Sender<()>)> {
     Ok((address, tx))
 }
 
+/// Starts a web server like [`serve`] which records the package downloads in `downloads`.
+fn serve_recording_downloads(
+    packages: &[TestPackage],
+    downloads: Arc<Mutex<Vec<PackageDownload>>>,
+) -> Result<(SocketAddr, Sender<()>)> {
+    let ip_address = default_ip_address()?;
+    let socket_address = SocketAddr::new(ip_address, 0);
+
+    let listener = std::net::TcpListener::bind(socket_address)?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+    let address = listener.local_addr()?;
+
+    let packages_cloned = packages.to_owned();
+    let downloads_cloned = downloads.clone();
+    let package_route = warp::path::full()
+        .and(warp::ext::get::<CompletedDownload>())
+        .and_then(
+            move |path: FullPath, completed_download: CompletedDownload| {
+                let packages = packages_cloned.to_owned();
+                let downloads = downloads_cloned.clone();
+
+                async move {
+                    find_package(&packages, &path)
+                        .map(|package| {
+                            Response::builder()
+                                .header(CONTENT_TYPE, HeaderValue::from_static("application/gzip"))
+                                .header(CONNECTION, HeaderValue::from_static("close"))
+                                .body(recorded_body(package, downloads, completed_download))
+                                .unwrap()
+                        })
+                        .ok_or_else(warp::reject::not_found)
+                }
+            },
+        );
+
+    let routes = metadata_route(packages).or(package_route);
+
+    // The connections are wrapped to record when the client closes
+    // them. The requests carry the slot of their connection where the
+    // completed download is noted.
+    let service = warp::service(routes);
+    let make_service = make_service_fn(move |connection: &RecordedConnection| {
+        let service = service.clone();
+        let completed_download = connection.completed_download.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut request: Request<Body>| {
+                request.extensions_mut().insert(completed_download.clone());
+                let mut service = service.clone();
+                async move { service.call(request).await }
+            }))
+        }
+    });
+
+    // Accept errors are skipped because an error in the stream would
+    // stop the server.
+    let connections = stream::unfold(listener, move |listener| {
+        let downloads = downloads.clone();
+        async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let connection = RecordedConnection::new(stream, downloads);
+                        return Some((Ok::<_, Infallible>(connection), listener));
+                    }
+                    Err(error) => {
+                        println!("Repository server could not accept a connection: {}", error);
+                        tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                    }
+                }
+            }
+        }
+    });
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let server = Server::builder(accept::from_stream(connections))
+        .serve(make_service)
+        .with_graceful_shutdown(async {
+            rx.await.ok();
+        });
+
+    tokio::task::spawn(server);
+
+    Ok((address, tx))
+}
+
+/// Creates a route which provides the repository metadata of the given test packages.
+fn metadata_route(
+    packages: &[TestPackage],
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let packages = packages.to_owned();
+    warp::path("metadata.json")
+        .map(move || warp::reply::json(&StackableRepositoryMetadata::from(packages.as_ref())))
+}
+
+/// Returns the test package with the given repository path.
+fn find_package<'a>(packages: &'a [TestPackage], path: &FullPath) -> Option<&'a TestPackage> {
+    packages
+        .iter()
+        .find(|package| format!("/{}", package.repository_path()) == path.as_str())
+}
+
+/// Creates a streamed body containing the given package and records the download in
+/// `downloads`.
+///
+/// When the whole package was handed over to the connection, the download is noted in
+/// `completed_download`, so that the connection records its end as soon as the client closes
+/// it.
+fn recorded_body(
+    package: &TestPackage,
+    downloads: Arc<Mutex<Vec<PackageDownload>>>,
+    completed_download: CompletedDownload,
+) -> Body {
+    let index = {
+        let mut downloads = downloads.lock().unwrap();
+        downloads.push(PackageDownload {
+            path: package.repository_path(),
+            start: Instant::now(),
+            end: None,
+        });
+        downloads.len() - 1
+    };
+
+    let chunks = package
+        .binary()
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| Ok::<_, Infallible>(chunk.to_vec()))
+        .collect::<Vec<_>>();
+
+    // The next chunk is requested as soon as the previous one was
+    // handed over, so the end of the stream marks that the whole
+    // package was handed over to the connection.
+    let completion = stream::once(async move {
+        *completed_download.0.lock().unwrap() = Some(index);
+    })
+    .filter_map(|_| async { None });
+
+    Body::wrap_stream(stream::iter(chunks).chain(completion))
+}
+
 /// Returns the IP address of a network interface which is up and which is not the loopback
 /// interface.
 ///
@@ -18,6 +18,12 @@ impl From<TestResult> for Result<()> {
 }
 
 impl TestResult {
+    /// Returns true if no error was applied yet
+    #[allow(dead_code)]
+    pub fn is_ok(&self) -> bool {
+        self.0.is_ok()
+    }
+
     /// Applies the AND operation to the given results
     ///
     /// If `result` contains already an error then `other_result` is
@@ -33,4 +39,22 @@ impl TestResult {
             }
         }
     }
+
+    /// Applies an error on this result if `actual` does not equal
+    /// `expected`
+    ///
+    /// The error message contains the given description of the
+    /// compared values.
+    #[allow(dead_code)]
+    pub fn check_eq<T>(&mut self, description: &str, expected: &T, actual: &T)
+    where
+        T: Debug + PartialEq + ?Sized,
+    {
+        if expected != actual {
+            self.combine::<(), _>(&Err(format!(
+                "{} is [{:?}] but [{:?}] was expected",
+                description, actual, expected
+            )));
+        }
+    }
 }
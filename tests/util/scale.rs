@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use integration_test_commons::test::prelude::*;
+
+use super::benchmark::{BenchmarkReport, PACKAGE_DOWNLOAD, POD_DELETION, POD_READY};
+use super::fixture::unique_name;
+use super::nodes::{free_pod_slots, stackable_node};
+use super::repository::StackableRepositoryBuilder;
+use super::status::{condition_status, verify_pod_condition};
+use super::test_package::TestPackage;
+
+/// Interval in which the pods are checked during the soak phase if no
+/// churn is configured
+const SOAK_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Parameters of a scale test
+///
+/// A scale test runs `waves` times through creating `pods` pods,
+/// waiting until they are ready, and deleting them. Afterwards the
+/// optional soak phase keeps `pods` pods running for `duration` and
+/// replaces `churn_rate` pods per minute.
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct ScaleConfig {
+    /// Number of pods which run at the same time
+    pub pods: usize,
+    /// Maximum number of pods which are created or deleted
+    /// concurrently
+    pub concurrency: usize,
+    /// Number of times all pods are created and deleted
+    pub waves: usize,
+    /// Number of pods per minute which are replaced in the soak phase
+    pub churn_rate: f64,
+    /// Duration of the soak phase; there is no soak phase if it is zero
+    pub duration: Duration,
+}
+
+#[allow(dead_code)]
+impl ScaleConfig {
+    /// Overrides the values of this configuration with the ones set in
+    /// the environment variables `AGENT_SCALE_PODS`,
+    /// `AGENT_SCALE_CONCURRENCY`, `AGENT_SCALE_WAVES`,
+    /// `AGENT_SCALE_CHURN_RATE`, and `AGENT_SCALE_DURATION_SECONDS`.
+    ///
+    /// The churn rate must be a finite, non-negative number.
+    pub fn with_env_overrides(self) -> Result<ScaleConfig> {
+        let churn_rate = env_var::<f64>("AGENT_SCALE_CHURN_RATE")?.unwrap_or(self.churn_rate);
+        if !churn_rate.is_finite() || churn_rate < 0.0 {
+            return Err(anyhow!(
+                "The churn rate must be a finite, non-negative number but is [{}]",
+                churn_rate
+            ));
+        }
+
+        Ok(ScaleConfig {
+            pods: env_var("AGENT_SCALE_PODS")?.unwrap_or(self.pods),
+            concurrency: env_var("AGENT_SCALE_CONCURRENCY")?.unwrap_or(self.concurrency),
+            waves: env_var("AGENT_SCALE_WAVES")?.unwrap_or(self.waves),
+            churn_rate,
+            duration: env_var("AGENT_SCALE_DURATION_SECONDS")?
+                .map(Duration::from_secs)
+                .unwrap_or(self.duration),
+        })
+    }
+}
+
+/// Parses the given environment variable if it is set.
+#[allow(dead_code)]
+pub fn env_var<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|error| anyhow!("The value [{}] of {} is invalid: {}", value, name, error)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(error) => Err(anyhow!("{} could not be read: {}", name, error)),
+    }
+}
+
+/// Phase of a scale test in which an error occurred
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Setup,
+    Create,
+    Ready,
+    Soak,
+    Delete,
+    Teardown,
+}
+
+/// Percentiles of a set of latencies
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+#[allow(dead_code)]
+impl Percentiles {
+    /// Calculates the percentiles with the nearest-rank method.
+    ///
+    /// `None` is returned if no latencies are given.
+    pub fn of(latencies: &[Duration]) -> Option<Percentiles> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        let mut latencies = latencies.to_vec();
+        latencies.sort();
+
+        let percentile = |percent: usize| {
+            let rank = (percent * latencies.len() + 99) / 100;
+            latencies[rank.max(1) - 1]
+        };
+
+        Some(Percentiles {
+            count: latencies.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: latencies[latencies.len() - 1],
+        })
+    }
+}
+
+impl Display for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} p50={:?} p90={:?} p99={:?} max={:?}",
+            self.count, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+/// Outcome of a scale test
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default)]
+pub struct ScaleReport {
+    pub pods_created: usize,
+    pub pods_ready: usize,
+    pub pods_deleted: usize,
+    /// Latencies from the creation of a pod until it is ready
+    pub create_to_ready: Vec<Duration>,
+    /// Latencies from the deletion of a pod until it is gone
+    pub delete_to_gone: Vec<Duration>,
+    /// Durations of the completed package downloads
+    pub package_downloads: Vec<Duration>,
+    pub errors: BTreeMap<Phase, Vec<String>>,
+}
+
+#[allow(dead_code)]
+impl ScaleReport {
+    fn record_error<E: Display>(&mut self, phase: Phase, error: E) {
+        self.errors
+            .entry(phase)
+            .or_default()
+            .push(error.to_string());
+    }
+
+    /// Adds the measured latencies to the given benchmark report.
+    pub fn add_to(&self, benchmark_report: &mut BenchmarkReport) {
+        benchmark_report.record_all(PACKAGE_DOWNLOAD, &self.package_downloads);
+        benchmark_report.record_all(POD_READY, &self.create_to_ready);
+        benchmark_report.record_all(POD_DELETION, &self.delete_to_gone);
+    }
+
+    /// Returns an error containing this report if an error occurred
+    /// during the scale test.
+    pub fn result(&self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("The scale test failed:\n{}", self))
+        }
+    }
+}
+
+impl Display for ScaleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Pods: {} created, {} ready, {} deleted",
+            self.pods_created, self.pods_ready, self.pods_deleted
+        )?;
+
+        for (description, latencies) in [
+            ("create→ready", &self.create_to_ready),
+            ("delete→gone", &self.delete_to_gone),
+        ]
+        .iter()
+        {
+            match Percentiles::of(latencies) {
+                Some(percentiles) => writeln!(f, "{}: {}", description, percentiles)?,
+                None => writeln!(f, "{}: no measurements", description)?,
+            }
+        }
+
+        for (phase, errors) in self.errors.iter() {
+            writeln!(f, "Errors in phase {:?}: {}", phase, errors.len())?;
+            for error in errors {
+                writeln!(f, "  {}", error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a scale test with pods of the given package on a Stackable
+/// node.
+///
+/// The pods are bound directly to the node. The test is aborted before
+/// any pod is created if the node cannot take the configured number of
+/// pods. All errors are recorded in the returned report.
+#[allow(dead_code)]
+pub async fn run_scale_test(
+    client: &KubeClient,
+    package: &TestPackage,
+    pod_name: &str,
+    config: &ScaleConfig,
+) -> ScaleReport {
+    let mut report = ScaleReport::default();
+
+    let node_name = match check_node(client, config).await {
+        Ok(node_name) => node_name,
+        Err(error) => {
+            report.record_error(Phase::Setup, error);
+            return report;
+        }
+    };
+
+    let mut repository_builder =
+        StackableRepositoryBuilder::new(&unique_name(&format!("{}-repository", pod_name)));
+    repository_builder.package(package);
+    repository_builder.record_downloads();
+    let repository_result = repository_builder.run(client).await;
+
+    match repository_result {
+        Ok(repository) => {
+            let scale_test = ScaleTest {
+                client,
+                config,
+                package,
+                pod_name,
+                node_name: &node_name,
+            };
+            scale_test.run(&mut report).await;
+
+            report.package_downloads = repository
+                .downloads()
+                .iter()
+                .filter_map(|download| Some(download.end? - download.start))
+                .collect();
+
+            if let Err(error) = repository.close(client).await {
+                report.record_error(Phase::Teardown, error);
+            }
+        }
+        Err(error) => report.record_error(Phase::Setup, error),
+    }
+
+    report
+}
+
+/// Returns the name of a Stackable node which can take the configured
+/// number of pods.
+async fn check_node(client: &KubeClient, config: &ScaleConfig) -> Result<String> {
+    let node = stackable_node(client).await?;
+    let node_name = node
+        .metadata
+        .name
+        .to_owned()
+        .ok_or_else(|| anyhow!("The node has no name"))?;
+
+    let free_pod_slots = free_pod_slots(&node).await? as usize;
+    if config.pods > free_pod_slots {
+        Err(anyhow!(
+            "The scale test tries to run {} pods but only {} pods can be started on the node {}",
+            config.pods,
+            free_pod_slots,
+            node_name
+        ))
+    } else {
+        Ok(node_name)
+    }
+}
+
+struct ScaleTest<'a> {
+    client: &'a KubeClient,
+    config: &'a ScaleConfig,
+    package: &'a TestPackage,
+    pod_name: &'a str,
+    node_name: &'a str,
+}
+
+impl<'a> ScaleTest<'a> {
+    async fn run(&self, report: &mut ScaleReport) {
+        for _ in 0..self.config.waves {
+            let pods = self.start_pods(report, self.config.pods).await;
+            self.stop_pods(report, pods).await;
+        }
+
+        if self.config.duration > Duration::ZERO {
+            self.soak(report).await;
+        }
+    }
+
+    /// Keeps the configured number of pods running for the configured
+    /// duration, replaces the oldest pods according to the churn rate,
+    /// and checks that the remaining pods stay ready.
+    async fn soak(&self, report: &mut ScaleReport) {
+        let mut pods = self.start_pods(report, self.config.pods).await;
+
+        // The interval is capped at the duration and the loop runs at
+        // least once, so that the pods are checked before they are
+        // stopped even if the churn rate is too low to replace a pod
+        // within the duration. The interval is capped before it is
+        // converted because a tiny churn rate yields an interval which
+        // exceeds the range of `Duration`.
+        let interval_seconds = if self.config.churn_rate > 0.0 {
+            60.0 / self.config.churn_rate
+        } else {
+            SOAK_CHECK_INTERVAL.as_secs_f64()
+        };
+        let interval = if interval_seconds < self.config.duration.as_secs_f64() {
+            Duration::from_secs_f64(interval_seconds)
+        } else {
+            self.config.duration
+        };
+
+        let start = Instant::now();
+        loop {
+            tokio::time::sleep(interval).await;
+
+            if self.config.churn_rate > 0.0 && !pods.is_empty() {
+                let oldest_pod = pods.remove(0);
+                self.stop_pods(report, vec![oldest_pod]).await;
+                pods.extend(self.start_pods(report, 1).await);
+            }
+
+            self.check_pods_ready(report, &pods).await;
+
+            if start.elapsed() + interval > self.config.duration {
+                break;
+            }
+        }
+
+        self.stop_pods(report, pods).await;
+    }
+
+    /// Creates the given number of pods and waits until they are ready.
+    ///
+    /// All created pods are returned, also the ones which did not
+    /// become ready, so that they can be deleted afterwards.
+    async fn start_pods(&self, report: &mut ScaleReport, count: usize) -> Vec<Pod> {
+        let outcomes = stream::iter(0..count)
+            .map(|_| self.start_pod())
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut pods = Vec::new();
+        for outcome in outcomes {
+            match outcome {
+                Ok((pod, ready_result)) => {
+                    report.pods_created += 1;
+                    match ready_result {
+                        Ok(latency) => {
+                            report.pods_ready += 1;
+                            report.create_to_ready.push(latency);
+                        }
+                        Err(error) => report.record_error(Phase::Ready, error),
+                    }
+                    pods.push(pod);
+                }
+                Err(error) => report.record_error(Phase::Create, error),
+            }
+        }
+        pods
+    }
+
+    /// Creates a pod and returns it together with the latency until it
+    /// was ready.
+    async fn start_pod(&self) -> Result<(Pod, Result<Duration>)> {
+        let mut pod_definition = self.package.pod(&unique_name(self.pod_name));
+        pod_definition
+            .spec
+            .get_or_insert_with(Default::default)
+            .node_name
+            .replace(String::from(self.node_name));
+        let pod_spec = serde_yaml::to_string(&pod_definition)?;
+
+        let start = Instant::now();
+        let pod = self.client.create::<Pod>(&pod_spec).await?;
+        let ready_result = verify_pod_condition(self.client, &pod, "Ready")
+            .await
+            .map(|_| start.elapsed());
+
+        Ok((pod, ready_result))
+    }
+
+    /// Deletes the given pods and waits until they are gone.
+    async fn stop_pods(&self, report: &mut ScaleReport, pods: Vec<Pod>) {
+        let outcomes = stream::iter(pods)
+            .map(|pod| async move {
+                let start = Instant::now();
+                self.client.delete(pod).await.map(|_| start.elapsed())
+            })
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        for outcome in outcomes {
+            match outcome {
+                Ok(latency) => {
+                    report.pods_deleted += 1;
+                    report.delete_to_gone.push(latency);
+                }
+                Err(error) => report.record_error(Phase::Delete, error),
+            }
+        }
+    }
+
+    /// Records an error for every given pod which is not ready anymore.
+    async fn check_pods_ready(&self, report: &mut ScaleReport, pods: &[Pod]) {
+        let outcomes = stream::iter(pods)
+            .map(|pod| self.client.get_status(pod))
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        for outcome in outcomes {
+            match outcome {
+                Ok(pod) if condition_status(&pod, "Ready").as_deref() == Some("True") => {}
+                Ok(pod) => report.record_error(
+                    Phase::Soak,
+                    format!(
+                        "The pod {} is not ready anymore: {:?}",
+                        pod.metadata.name.as_deref().unwrap_or_default(),
+                        pod.status
+                    ),
+                ),
+                Err(error) => report.record_error(Phase::Soak, error),
+            }
+        }
+    }
+}
@@ -1,3 +1,29 @@
+//! Catalogue of the test packages
+//!
+//! All test packages are served by the tests themselves with a
+//! [`super::repository::StackableRepositoryBuilder`], so no package
+//! must be hosted outside of this repository:
+//!
+//! - [`echo_service`] prints the content of `LOG_OUTPUT` and sleeps.
+//! - [`exit_service`] terminates with a fixed exit code.
+//! - [`configurable_exit_service`] terminates with the exit code given
+//!   in `EXIT_CODE` or with the signal given in `SIGNAL`.
+//! - [`noop_service`] just sleeps.
+//! - [`nostop_service`] sleeps and ignores SIGINT and SIGTERM.
+//! - [`http_echo_service`] answers HTTP requests with its identity and
+//!   environment.
+//! - [`signal_recorder_service`] records the received signals.
+//! - [`forking_service`] spawns children, grandchildren, and a daemon.
+//! - [`flaky_service`] fails on the first starts and then keeps running.
+//! - [`env_dump_job`] prints its environment and the state of its
+//!   kubeconfig as JSON.
+//! - [`ticker_service`] prints a numbered line at a fixed interval.
+//! - [`log_flood_service`] prints numbered lines at a high rate.
+//! - [`marker_job`] writes marker files, e.g. as init container.
+//! - [`marker_service`] checks the marker files written by marker jobs.
+//! - [`unit_timestamps_job`] prints the state transition timestamps of
+//!   systemd units.
+
 use integration_test_commons::test::prelude::*;
 
 use super::test_package::TestPackage;
@@ -65,6 +91,35 @@ pub fn exit_service(exit_code: i8) -> TestPackage {
     }
 }
 
+/// The exit-service terminates immediately with the exit code given in
+/// the environment variable `EXIT_CODE`.
+///
+/// If `EXIT_CODE` is not set then the service terminates successfully.
+/// If the environment variable `SIGNAL` is set, e.g. to `KILL` or
+/// `SEGV`, then the service sends this signal to itself instead.
+#[allow(dead_code)]
+pub fn configurable_exit_service() -> TestPackage {
+    TestPackage {
+        name: String::from("exit-service"),
+        version: String::from("1.0.1"),
+        job: true,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            if [ -n "$SIGNAL" ]; then
+                kill -s "$SIGNAL" $$
+            fi
+
+            exit "${EXIT_CODE:-0}"
+            "#
+        )),
+    }
+}
+
 /// This service performs no operation and just sleeps.
 #[allow(dead_code)]
 pub fn noop_service() -> TestPackage {
@@ -112,3 +167,842 @@ pub fn nostop_service() -> TestPackage {
         )),
     }
 }
+
+/// The http-echo-service listens on the port given in the environment
+/// variable `PORT` (default: 8080) on all interfaces and answers every
+/// GET request with a JSON document containing its identity and
+/// environment:
+///
+/// ```json
+/// {
+///   "podName": "<content of POD_NAME>",
+///   "hostname": "<hostname>",
+///   "pid": 1234,
+///   "path": "<requested path>",
+///   "environment": { "PORT": "8080", ... }
+/// }
+/// ```
+///
+/// The readiness of the service can be toggled at runtime with the file
+/// given in the environment variable `READY_FILE`:
+///
+/// - `GET /ready` responds with status 200 if `READY_FILE` is not set or
+///   the file exists, otherwise with status 503.
+/// - `GET /ready/on` creates the file.
+/// - `GET /ready/off` removes the file.
+///
+/// The file is not created on startup, so the service starts as not
+/// ready if `READY_FILE` is set. The existence of the file can also be
+/// checked with an exec probe.
+///
+/// The service can also become unhealthy while it keeps running:
+///
+/// - `GET /healthy` responds with status 200 as long as the service is
+///   healthy, otherwise with status 500.
+/// - If the environment variable `UNHEALTHY_AFTER` is set then the
+///   service becomes unhealthy the given number of seconds after its
+///   start.
+/// - If the environment variable `HEALTHY_FILE` is set then the file is
+///   created on startup and removed when the service becomes unhealthy.
+///
+/// The service is implemented in Python and runs with Python 3 or, if
+/// not available, with Python 2.
+#[allow(dead_code)]
+pub fn http_echo_service() -> TestPackage {
+    TestPackage {
+        name: String::from("http-echo-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            if command -v python3 > /dev/null; then
+                PYTHON=python3
+            else
+                PYTHON=python
+            fi
+
+            exec "$PYTHON" - <<'EOF'
+            import json
+            import os
+            import socket
+            import sys
+            import threading
+
+            try:
+                from http.server import BaseHTTPRequestHandler, HTTPServer
+            except ImportError:
+                from BaseHTTPServer import BaseHTTPRequestHandler, HTTPServer
+
+
+            READY_FILE = os.environ.get("READY_FILE")
+            HEALTHY_FILE = os.environ.get("HEALTHY_FILE")
+            UNHEALTHY_AFTER = os.environ.get("UNHEALTHY_AFTER")
+
+            health = {"healthy": True}
+
+
+            def become_unhealthy():
+                health["healthy"] = False
+                if HEALTHY_FILE is not None and os.path.exists(HEALTHY_FILE):
+                    os.remove(HEALTHY_FILE)
+                print("http-echo-service became unhealthy")
+                sys.stdout.flush()
+
+
+            class Handler(BaseHTTPRequestHandler):
+                def respond(self, status, text):
+                    body = text.encode("utf-8")
+                    self.send_response(status)
+                    self.send_header("Content-Type", "text/plain")
+                    self.send_header("Content-Length", str(len(body)))
+                    self.end_headers()
+                    self.wfile.write(body)
+
+                def do_GET(self):
+                    if self.path == "/healthy":
+                        if health["healthy"]:
+                            self.respond(200, "healthy")
+                        else:
+                            self.respond(500, "unhealthy")
+                        return
+
+                    if self.path == "/ready":
+                        if READY_FILE is None or os.path.exists(READY_FILE):
+                            self.respond(200, "ready")
+                        else:
+                            self.respond(503, "not ready")
+                        return
+
+                    if self.path == "/ready/on" and READY_FILE is not None:
+                        open(READY_FILE, "w").close()
+                        self.respond(200, "ready")
+                        return
+
+                    if self.path == "/ready/off" and READY_FILE is not None:
+                        if os.path.exists(READY_FILE):
+                            os.remove(READY_FILE)
+                        self.respond(200, "not ready")
+                        return
+
+                    body = json.dumps({
+                        "podName": os.environ.get("POD_NAME"),
+                        "hostname": socket.gethostname(),
+                        "pid": os.getpid(),
+                        "path": self.path,
+                        "environment": dict(os.environ),
+                    }).encode("utf-8")
+
+                    self.send_response(200)
+                    self.send_header("Content-Type", "application/json")
+                    self.send_header("Content-Length", str(len(body)))
+                    self.end_headers()
+                    self.wfile.write(body)
+
+
+            if HEALTHY_FILE is not None:
+                open(HEALTHY_FILE, "w").close()
+
+            if UNHEALTHY_AFTER is not None:
+                timer = threading.Timer(float(UNHEALTHY_AFTER), become_unhealthy)
+                timer.daemon = True
+                timer.start()
+
+            port = int(os.environ.get("PORT", "8080"))
+            server = HTTPServer(("", port), Handler)
+
+            print("http-echo-service listening on port %d" % port)
+            sys.stdout.flush()
+
+            server.serve_forever()
+            EOF
+            "#
+        )),
+    }
+}
+
+/// The signal-recorder-service records every received signal with a
+/// timestamp.
+///
+/// The signals SIGHUP, SIGINT, SIGQUIT, SIGUSR1, SIGUSR2, and SIGTERM
+/// are trapped and appended as `<signal> <seconds since epoch>` to
+/// standard output and to the file given in the environment variable
+/// `SIGNAL_LOG`. The line `STARTED <seconds since epoch>` is written on
+/// startup.
+///
+/// SIGKILL cannot be trapped, therefore the service writes a heartbeat
+/// timestamp every 100 ms to the file `$SIGNAL_LOG.heartbeat`. The last
+/// heartbeat approximates the point in time when the service was
+/// killed.
+///
+/// The service ignores all trapped signals unless the environment
+/// variable `EXIT_ON_SIGNAL` names the signal, e.g. `TERM`, in which
+/// case the service terminates after recording it.
+#[allow(dead_code)]
+pub fn signal_recorder_service() -> TestPackage {
+    TestPackage {
+        name: String::from("signal-recorder-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            SIGNAL_LOG=${SIGNAL_LOG:-/tmp/signal-recorder-service.log}
+
+            record() {
+                echo "$1 $(date +%s.%N)" | tee -a "$SIGNAL_LOG"
+
+                if [ "$1" = "$EXIT_ON_SIGNAL" ]; then
+                    exit 0
+                fi
+            }
+
+            for signal in HUP INT QUIT USR1 USR2 TERM; do
+                trap "record $signal" "$signal"
+            done
+
+            record STARTED
+
+            while true; do
+                date +%s.%N > "$SIGNAL_LOG.heartbeat"
+                sleep 0.1
+            done
+            "#
+        )),
+    }
+}
+
+/// The forking-service spawns a tree of background processes and falls
+/// asleep.
+///
+/// The following processes are started and their PIDs are written to
+/// standard output:
+///
+/// - a child process (`child <PID>`),
+/// - a child process which spawns a grandchild (`child <PID>` and
+///   `grandchild <PID>`),
+/// - a daemon which is detached by a double fork and runs in its own
+///   session (`daemon <PID>`).
+///
+/// Including the service itself and its sleep, the tree consists of
+/// six processes. All of them inherit the environment of the service,
+/// so they can be identified by a marker in the environment.
+///
+/// If the environment variable `EXIT_AFTER` is set then the service
+/// terminates with exit code 1 after the given number of seconds while
+/// the spawned processes keep running.
+#[allow(dead_code)]
+pub fn forking_service() -> TestPackage {
+    TestPackage {
+        name: String::from("forking-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            sleep 1d &
+            echo "child $!"
+
+            sh -c 'sleep 1d & echo "grandchild $!"; wait' &
+            echo "child $!"
+
+            sh -c 'setsid sleep 1d < /dev/null > /dev/null 2>&1 & echo "daemon $!"'
+
+            if [ -n "$EXIT_AFTER" ]; then
+                sleep "$EXIT_AFTER"
+                exit 1
+            fi
+
+            sleep 1d
+            "#
+        )),
+    }
+}
+
+/// The flaky-service fails on the first starts and keeps running
+/// afterwards.
+///
+/// The number of starts is counted in the file given in the environment
+/// variable `COUNTER_FILE` which persists across restarts. The current
+/// run number is written as `run <number>` to standard output. The
+/// service terminates with exit code 1 as long as the run number does
+/// not exceed the number given in the environment variable
+/// `FAILING_STARTS` (default: 0).
+#[allow(dead_code)]
+pub fn flaky_service() -> TestPackage {
+    TestPackage {
+        name: String::from("flaky-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            COUNTER_FILE=${COUNTER_FILE:-/tmp/flaky-service.counter}
+            FAILING_STARTS=${FAILING_STARTS:-0}
+
+            run=$(( $(cat "$COUNTER_FILE" 2> /dev/null || echo 0) + 1 ))
+            echo "$run" > "$COUNTER_FILE"
+
+            echo "run $run"
+
+            if [ "$run" -le "$FAILING_STARTS" ]; then
+                exit 1
+            fi
+
+            sleep 1d
+            "#
+        )),
+    }
+}
+
+/// The env-dump-job prints its environment and the state of its
+/// kubeconfig as a single JSON line and terminates.
+///
+/// The JSON document is enclosed in the lines `ENV_DUMP_BEGIN` and
+/// `ENV_DUMP_END` and can be parsed with
+/// [`super::env_dump::EnvDump::from_logs`]. It contains the following
+/// fields:
+///
+/// - `environment`: all environment variables
+/// - `kubeconfigKeys`: the keys which occur in the file `KUBECONFIG`
+///   points to, without their values, or `null` if it could not be read
+/// - `kubeconfigError`: the reason why the file could not be read
+/// - `kubeconfigFiles`: whether the files referenced in the kubeconfig
+///   are readable and their SHA-256 hashes
+/// - `kubeconfigAccessReview`: the outcome of a SelfSubjectAccessReview
+///   which the job sends to the API server with the credentials from
+///   the kubeconfig, or `null` if the kubeconfig could not be read
+///
+/// The credentials never leave the node; neither the values in the
+/// kubeconfig nor the content of the referenced files are printed.
+///
+/// The document is ASCII-only, non-ASCII characters are escaped.
+///
+/// The job is implemented in Python and runs with Python 3 or, if not
+/// available, with Python 2.
+#[allow(dead_code)]
+pub fn env_dump_job() -> TestPackage {
+    TestPackage {
+        name: String::from("env-dump-job"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            if command -v python3 > /dev/null; then
+                PYTHON=python3
+            else
+                PYTHON=python
+            fi
+
+            exec "$PYTHON" - <<'EOF'
+            import base64
+            import hashlib
+            import json
+            import os
+            import re
+            import shutil
+            import ssl
+            import sys
+            import tempfile
+
+            try:
+                from urllib.error import HTTPError
+                from urllib.request import Request, urlopen
+            except ImportError:
+                from urllib2 import HTTPError, Request, urlopen
+
+            try:
+                environ = os.environb
+            except AttributeError:
+                environ = os.environ
+
+
+            def text(value):
+                return value.decode("utf-8", "replace")
+
+            kubeconfig = None
+            kubeconfig_error = None
+            try:
+                with open(os.environ["KUBECONFIG"]) as kubeconfig_file:
+                    kubeconfig = kubeconfig_file.read()
+            except Exception as error:
+                kubeconfig_error = str(error)
+
+
+
+            def kubeconfig_value(key):
+                match = re.search(
+                    r"^[\s-]*" + re.escape(key) + r":\s*[\"']?([^\"'\s]+)",
+                    kubeconfig,
+                    re.MULTILINE,
+                )
+                return match.group(1) if match else None
+
+
+            def kubeconfig_path(key):
+                path = kubeconfig_value(key)
+                if path is None:
+                    return None
+                return os.path.join(os.path.dirname(os.environ["KUBECONFIG"]), path)
+
+
+            def review_access():
+                """Sends a SelfSubjectAccessReview with the credentials from the
+                kubeconfig; every authenticated user may create one."""
+                temp_dir = tempfile.mkdtemp()
+                try:
+                    def credential_file(key):
+                        path = kubeconfig_path(key)
+                        data = kubeconfig_value(key + "-data")
+                        if path is None and data is not None:
+                            path = os.path.join(temp_dir, key)
+                            with open(path, "wb") as credential:
+                                credential.write(base64.b64decode(data))
+                        return path
+
+                    server = kubeconfig_value("server")
+                    if server is None:
+                        raise Exception("The kubeconfig does not contain a server")
+
+                    context = ssl.create_default_context(
+                        cafile=credential_file("certificate-authority")
+                    )
+                    if kubeconfig_value("insecure-skip-tls-verify") == "true":
+                        context.check_hostname = False
+                        context.verify_mode = ssl.CERT_NONE
+                    client_certificate = credential_file("client-certificate")
+                    if client_certificate is not None:
+                        context.load_cert_chain(
+                            client_certificate, credential_file("client-key")
+                        )
+
+                    headers = {"Content-Type": "application/json"}
+                    token = kubeconfig_value("token")
+                    token_file = kubeconfig_path("tokenFile")
+                    if token is None and token_file is not None:
+                        with open(token_file) as token_content:
+                            token = token_content.read().strip()
+                    if token is not None:
+                        headers["Authorization"] = "Bearer " + token
+
+                    review = {
+                        "apiVersion": "authorization.k8s.io/v1",
+                        "kind": "SelfSubjectAccessReview",
+                        "spec": {"resourceAttributes": {"verb": "get", "resource": "pods"}},
+                    }
+                    request = Request(
+                        server.rstrip("/")
+                        + "/apis/authorization.k8s.io/v1/selfsubjectaccessreviews",
+                        data=json.dumps(review).encode("ascii"),
+                        headers=headers,
+                    )
+                    try:
+                        response = urlopen(request, context=context, timeout=10)
+                    except HTTPError as error:
+                        return {"status": error.code, "allowed": None, "error": str(error)}
+                    content = json.loads(response.read().decode("utf-8"))
+                    return {
+                        "status": response.getcode(),
+                        "allowed": content.get("status", {}).get("allowed", False),
+                        "error": None,
+                    }
+                except Exception as error:
+                    return {"status": None, "allowed": None, "error": str(error)}
+                finally:
+                    shutil.rmtree(temp_dir, ignore_errors=True)
+
+
+            kubeconfig_keys = None
+            kubeconfig_files = {}
+            kubeconfig_access_review = None
+            if kubeconfig is not None:
+                for key in ["certificate-authority", "client-certificate", "client-key", "tokenFile"]:
+                    path = kubeconfig_path(key)
+                    if path is None:
+                        continue
+                    try:
+                        with open(path, "rb") as referenced_file:
+                            sha256 = hashlib.sha256(referenced_file.read()).hexdigest()
+                        kubeconfig_files[kubeconfig_value(key)] = {
+                            "readable": True,
+                            "sha256": sha256,
+                        }
+                    except Exception:
+                        kubeconfig_files[kubeconfig_value(key)] = {
+                            "readable": False,
+                            "sha256": None,
+                        }
+
+                kubeconfig_access_review = review_access()
+
+                kubeconfig_keys = sorted(
+                    set(re.findall(r"^[\s-]*([\w-]+):", kubeconfig, re.MULTILINE))
+                )
+
+            dump = {
+                "environment": dict(
+                    (text(key), text(value)) for key, value in environ.items()
+                ),
+                "kubeconfigKeys": kubeconfig_keys,
+                "kubeconfigError": kubeconfig_error,
+                "kubeconfigFiles": kubeconfig_files,
+                "kubeconfigAccessReview": kubeconfig_access_review,
+            }
+
+            print("ENV_DUMP_BEGIN")
+            print(json.dumps(dump, sort_keys=True))
+            print("ENV_DUMP_END")
+            sys.stdout.flush()
+            EOF
+            "#
+        )),
+    }
+}
+
+/// The ticker-service prints the lines `tick 1`, `tick 2`, and so on
+/// with the interval in seconds given in the environment variable
+/// `INTERVAL`.
+///
+/// If `INTERVAL` is not set then one line per second is printed. If the
+/// environment variable `COUNT` is set then the service stops printing
+/// after the given number of lines and falls asleep.
+#[allow(dead_code)]
+pub fn ticker_service() -> TestPackage {
+    TestPackage {
+        name: String::from("ticker-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            tick=1
+
+            while [ -z "$COUNT" ] || [ "$tick" -le "$COUNT" ]; do
+                echo "tick $tick"
+                tick=$((tick + 1))
+                sleep "${INTERVAL:-1}"
+            done
+
+            sleep 1d
+            "#
+        )),
+    }
+}
+
+/// The log-flood-service prints numbered lines as fast as possible or
+/// with a given rate and falls asleep.
+///
+/// The following environment variables are recognized:
+///
+/// - `LINES`: number of lines (default: 1000)
+/// - `LINE_LENGTH`: length of a line in bytes without the line break
+///   (default: 80)
+/// - `RATE`: lines per second; 0 means unlimited (default: 0)
+/// - `STREAM`: `stdout` or `stderr` (default: `stdout`)
+/// - `INVALID_UTF8`: if set to `true` then every line contains the
+///   invalid UTF-8 sequence [`INVALID_UTF8_SEQUENCE`]
+///
+/// Every line starts with its number padded to eight digits followed by
+/// a space. The rest of the line is filled with a lowercase letter which
+/// depends on the line number, see [`log_flood_line`]. If
+/// `INVALID_UTF8` is set then the line continues with `before `, the
+/// invalid sequence, and ` after` instead.
+///
+/// After all lines are printed, the line `FLOOD_END` is printed to the
+/// same stream.
+///
+/// The service is implemented in Python and runs with Python 3 or, if
+/// not available, with Python 2.
+#[allow(dead_code)]
+pub fn log_flood_service() -> TestPackage {
+    TestPackage {
+        name: String::from("log-flood-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            if command -v python3 > /dev/null; then
+                PYTHON=python3
+            else
+                PYTHON=python
+            fi
+
+            "$PYTHON" - <<'EOF'
+            import os
+            import sys
+            import time
+
+            lines = int(os.environ.get("LINES", "1000"))
+            line_length = int(os.environ.get("LINE_LENGTH", "80"))
+            rate = float(os.environ.get("RATE", "0"))
+            invalid_utf8 = os.environ.get("INVALID_UTF8") == "true"
+
+            stream = sys.stderr if os.environ.get("STREAM") == "stderr" else sys.stdout
+            output = getattr(stream, "buffer", stream)
+
+            start = time.time()
+
+            for number in range(1, lines + 1):
+                prefix = ("%08d " % number).encode("ascii")
+                if invalid_utf8:
+                    line = prefix + b"before \xff\xc3\x28\xe2\x82 after"
+                else:
+                    filler = bytearray([ord("a") + number % 26])
+                    line = prefix + bytes(filler) * max(line_length - len(prefix), 0)
+                output.write(line + b"\n")
+
+                if rate > 0:
+                    delay = start + number / rate - time.time()
+                    if delay > 0:
+                        output.flush()
+                        time.sleep(delay)
+
+            output.write(b"FLOOD_END\n")
+            output.flush()
+            EOF
+
+            sleep 1d
+            "#
+        )),
+    }
+}
+
+/// Invalid UTF-8 sequence printed by the log-flood-service
+///
+/// It contains an invalid start byte, a start byte followed by an ASCII
+/// character, and a truncated sequence.
+#[allow(dead_code)]
+pub const INVALID_UTF8_SEQUENCE: &[u8] = b"\xff\xc3\x28\xe2\x82";
+
+/// Returns the line with the given number as printed by the
+/// log-flood-service without `INVALID_UTF8`.
+#[allow(dead_code)]
+pub fn log_flood_line(number: usize, line_length: usize) -> String {
+    let prefix = format!("{:08} ", number);
+    let filler = char::from(b'a' + (number % 26) as u8);
+    let filler_length = line_length.saturating_sub(prefix.len());
+    prefix + &filler.to_string().repeat(filler_length)
+}
+
+/// The marker-job records its run in marker files and terminates.
+///
+/// The files `$MARKER_DIR/$MARKER_NAME.start` and
+/// `$MARKER_DIR/$MARKER_NAME.end` contain the points in time in seconds
+/// since epoch when the job started and finished. The job runs for the
+/// number of seconds given in `DURATION` (default: 2) and terminates
+/// with the exit code given in `EXIT_CODE` (default: 0). The end marker
+/// is only written on success.
+#[allow(dead_code)]
+pub fn marker_job() -> TestPackage {
+    TestPackage {
+        name: String::from("marker-job"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            mkdir -p "$MARKER_DIR"
+            date +%s > "$MARKER_DIR/$MARKER_NAME.start"
+            echo "$MARKER_NAME started"
+
+            sleep "${DURATION:-2}"
+
+            if [ "${EXIT_CODE:-0}" -ne 0 ]; then
+                echo "$MARKER_NAME failed"
+                exit "$EXIT_CODE"
+            fi
+
+            date +%s > "$MARKER_DIR/$MARKER_NAME.end"
+            echo "$MARKER_NAME finished"
+            "#
+        )),
+    }
+}
+
+/// The marker-service verifies the marker files written by marker jobs
+/// and falls asleep.
+///
+/// The names of the expected markers are given in the environment
+/// variable `REQUIRED_MARKERS` separated by spaces. The service
+/// terminates with exit code 1 if a marker job did not finish, if a
+/// marker job started before its predecessor finished, or if the
+/// service started before the last marker job finished. Otherwise it
+/// records its start in `$MARKER_DIR/main.start`.
+#[allow(dead_code)]
+pub fn marker_service() -> TestPackage {
+    TestPackage {
+        name: String::from("marker-service"),
+        version: String::from("1.0.0"),
+        job: false,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            now=$(date +%s)
+            previous_end=0
+
+            for marker in $REQUIRED_MARKERS; do
+                if [ ! -f "$MARKER_DIR/$marker.end" ]; then
+                    echo "marker $marker did not finish"
+                    exit 1
+                fi
+
+                start=$(cat "$MARKER_DIR/$marker.start")
+                end=$(cat "$MARKER_DIR/$marker.end")
+
+                if [ "$start" -lt "$previous_end" ]; then
+                    echo "marker $marker started at $start before its predecessor finished at $previous_end"
+                    exit 1
+                fi
+
+                echo "marker $marker ran from $start to $end"
+                previous_end=$end
+            done
+
+            if [ "$now" -lt "$previous_end" ]; then
+                echo "service started at $now before the markers finished at $previous_end"
+                exit 1
+            fi
+
+            echo "$now" > "$MARKER_DIR/main.start"
+            echo "all markers present"
+
+            sleep 1d
+            "#
+        )),
+    }
+}
+
+/// The unit-timestamps-job prints the state transition timestamps of
+/// the systemd units whose name, description, or start command contains
+/// the content of the environment variable `UNIT_MATCH`.
+///
+/// The output is a JSON array enclosed by the lines
+/// `UNIT_TIMESTAMPS_BEGIN` and `UNIT_TIMESTAMPS_END` which can be parsed
+/// with [`super::units::UnitTimestamps::from_logs`]. Every element
+/// contains the following fields:
+///
+/// - `unit`: the name of the unit
+/// - `inactiveExit`: the time when the unit was started
+/// - `activeEnter`: the time when the unit became active
+///
+/// The times are given in seconds since the Unix epoch with microsecond
+/// precision or as `null` if the transition did not happen yet. They
+/// are derived from the monotonic timestamps of systemd because the
+/// realtime timestamps are only shown with a precision of seconds.
+#[allow(dead_code)]
+pub fn unit_timestamps_job() -> TestPackage {
+    TestPackage {
+        name: String::from("unit-timestamps-job"),
+        version: String::from("1.0.0"),
+        job: true,
+        script: String::from(indoc!(
+            r#"
+            #!/bin/sh
+
+            # Adding /run/current-system/sw/bin to PATH for NixOS support
+            PATH=$PATH:/run/current-system/sw/bin
+
+            exec python3 - <<'EOF'
+            import json
+            import os
+            import subprocess
+            import time
+
+            PROPERTIES = [
+                "Id",
+                "Description",
+                "ExecStart",
+                "InactiveExitTimestampMonotonic",
+                "ActiveEnterTimestampMonotonic",
+            ]
+
+
+            def systemctl(*args):
+                output = subprocess.check_output(("systemctl",) + args)
+                return output.decode("utf-8", "replace")
+
+
+            def realtime(monotonic_usec):
+                if int(monotonic_usec or 0) == 0:
+                    return None
+                elapsed = time.monotonic() - int(monotonic_usec) / 1e6
+                return round(time.time() - elapsed, 6)
+
+
+            units = [
+                line.split()[0]
+                for line in systemctl(
+                    "list-units", "--all", "--type=service", "--plain", "--no-legend"
+                ).splitlines()
+                if line.strip()
+            ]
+
+            arguments = ["show"]
+            for name in PROPERTIES:
+                arguments += ["-p", name]
+
+            unit_match = os.environ["UNIT_MATCH"]
+            timestamps = []
+            for block in systemctl(*(arguments + units)).split("\n\n"):
+                properties = dict(
+                    line.split("=", 1) for line in block.splitlines() if "=" in line
+                )
+                if any(unit_match in properties.get(name, "") for name in PROPERTIES[:3]):
+                    timestamps.append(
+                        {
+                            "unit": properties["Id"],
+                            "inactiveExit": realtime(
+                                properties.get("InactiveExitTimestampMonotonic")
+                            ),
+                            "activeEnter": realtime(
+                                properties.get("ActiveEnterTimestampMonotonic")
+                            ),
+                        }
+                    )
+
+            print("UNIT_TIMESTAMPS_BEGIN")
+            print(json.dumps(timestamps))
+            print("UNIT_TIMESTAMPS_END")
+            EOF
+            "#
+        )),
+    }
+}
@@ -0,0 +1,640 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use k8s_openapi::api::core::v1::ContainerStatus;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use k8s_openapi::chrono::{DateTime, Utc};
+
+/// Interval in which the pod status is polled while waiting for a
+/// status
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Waits until the given condition of the given pod is `True` and
+/// validates the pod status which fulfilled the condition with
+/// [`validate_pod_status`].
+#[allow(dead_code)]
+pub async fn verify_pod_condition(
+    client: &KubeClient,
+    pod: &Pod,
+    condition_type: &str,
+) -> Result<()> {
+    verify_status(client, pod, |pod| {
+        condition_status(pod, condition_type).as_deref() == Some("True")
+    })
+    .await
+    .map_err(|error| {
+        anyhow!(
+            "The condition {} of the pod did not become True: {}",
+            condition_type,
+            error
+        )
+    })
+}
+
+/// Waits until the given predicate holds for the given pod and
+/// validates the pod status which fulfilled the predicate with
+/// [`validate_pod_status`].
+///
+/// The status is polled until the timeout `verify_status` of the client
+/// is reached. Errors while retrieving the status are retried until the
+/// timeout, so that a transient API error does not fail the check. The
+/// validated status is the one the predicate was evaluated on, so a
+/// status change after the predicate held cannot cause a wrong result.
+#[allow(dead_code)]
+pub async fn verify_status<P>(client: &KubeClient, pod: &Pod, predicate: P) -> Result<()>
+where
+    P: Fn(&Pod) -> bool,
+{
+    let pod_name = pod.metadata.name.as_deref().unwrap_or_default();
+    let timeout = client.timeouts.verify_status;
+    let start = Instant::now();
+
+    let pod = loop {
+        match client.get_status(pod).await {
+            Ok(current_pod) if predicate(&current_pod) => break current_pod,
+            Ok(current_pod) if start.elapsed() > timeout => {
+                return Err(anyhow!(
+                    "The pod [{}] did not reach the expected status within {:?}: {:?}",
+                    pod_name,
+                    timeout,
+                    current_pod.status
+                ))
+            }
+            Err(error) if start.elapsed() > timeout => {
+                return Err(anyhow!(
+                    "The status of pod [{}] could not be retrieved within {:?}: {}",
+                    pod_name,
+                    timeout,
+                    error
+                ))
+            }
+            _ => {}
+        }
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    };
+
+    validate_pod_status(&pod).map_err(|error| {
+        anyhow!(
+            "The status of pod [{}] violates the Kubernetes invariants: {}",
+            pod_name,
+            error
+        )
+    })
+}
+
+/// Checks the status of the given pod against the invariants which
+/// Kubernetes guarantees for every pod:
+///
+/// - The phase is one of `Pending`, `Running`, `Succeeded`, `Failed`,
+///   and `Unknown`.
+/// - Every condition type occurs at most once and has the status
+///   `True`, `False`, or `Unknown`.
+/// - A ready pod is running and its containers are ready if the
+///   condition `ContainersReady` is reported at all.
+/// - A terminated pod is not ready.
+/// - Every container in the spec has exactly one container status once
+///   the pod is running or terminated, and there are no statuses for
+///   other containers.
+/// - A container status has at most one state and a ready container
+///   is running.
+/// - The QoS class is `BestEffort` for pods without resource requests
+///   and limits, otherwise `Burstable` or `Guaranteed`.
+/// - The host IP and the pod IPs are valid IP addresses and are set
+///   once the pod is running. The first entry in `podIPs` equals
+///   `podIP`.
+#[allow(dead_code)]
+pub fn validate_pod_status(pod: &Pod) -> Result<()> {
+    let spec = pod
+        .spec
+        .as_ref()
+        .ok_or_else(|| anyhow!("The pod spec is not set"))?;
+    let status = pod
+        .status
+        .as_ref()
+        .ok_or_else(|| anyhow!("The pod status is not set"))?;
+
+    let phase = status
+        .phase
+        .as_deref()
+        .ok_or_else(|| anyhow!("The phase is not set"))?;
+    if !["Pending", "Running", "Succeeded", "Failed", "Unknown"].contains(&phase) {
+        return Err(anyhow!("The phase [{}] is not valid", phase));
+    }
+
+    let mut condition_types = BTreeSet::new();
+    for condition in status.conditions.iter().flatten() {
+        if !condition_types.insert(condition.type_.as_str()) {
+            return Err(anyhow!(
+                "The condition {} occurs more than once",
+                condition.type_
+            ));
+        }
+        if !["True", "False", "Unknown"].contains(&condition.status.as_str()) {
+            return Err(anyhow!(
+                "The status [{}] of the condition {} is not valid",
+                condition.status,
+                condition.type_
+            ));
+        }
+    }
+
+    if condition_status(pod, "Ready").as_deref() == Some("True") {
+        if phase != "Running" {
+            return Err(anyhow!("The pod is ready but its phase is {}", phase));
+        }
+        if let Some(containers_ready) = condition_status(pod, "ContainersReady") {
+            if containers_ready != "True" {
+                return Err(anyhow!(
+                    "The pod is ready but the condition ContainersReady is {}",
+                    containers_ready
+                ));
+            }
+        }
+    }
+
+    let terminated = phase == "Succeeded" || phase == "Failed";
+
+    if phase == "Running" || terminated {
+        check_container_statuses(
+            "containers",
+            &spec.containers,
+            status.container_statuses.as_deref().unwrap_or_default(),
+        )?;
+        check_container_statuses(
+            "init containers",
+            spec.init_containers.as_deref().unwrap_or_default(),
+            status
+                .init_container_statuses
+                .as_deref()
+                .unwrap_or_default(),
+        )?;
+    }
+
+    let container_statuses = status
+        .init_container_statuses
+        .iter()
+        .chain(status.container_statuses.iter())
+        .flatten();
+    for container_status in container_statuses {
+        check_container_state(container_status, terminated)?;
+    }
+
+    check_qos_class(spec, status.qos_class.as_deref())?;
+
+    if let Some(host_ip) = &status.host_ip {
+        check_ip("host IP", host_ip)?;
+    }
+    if let Some(pod_ip) = &status.pod_ip {
+        check_ip("pod IP", pod_ip)?;
+    }
+    for pod_ip in status.pod_ips.iter().flatten() {
+        if let Some(ip) = &pod_ip.ip {
+            check_ip("entry in podIPs", ip)?;
+        }
+    }
+
+    if phase == "Running" {
+        if status.host_ip.is_none() {
+            return Err(anyhow!("The pod is running but the host IP is not set"));
+        }
+        if status.pod_ip.is_none() {
+            return Err(anyhow!("The pod is running but the pod IP is not set"));
+        }
+    }
+
+    let first_pod_ip = status
+        .pod_ips
+        .as_ref()
+        .and_then(|pod_ips| pod_ips.first())
+        .and_then(|pod_ip| pod_ip.ip.as_ref());
+    if first_pod_ip.is_some() && first_pod_ip != status.pod_ip.as_ref() {
+        return Err(anyhow!(
+            "The first entry in podIPs [{:?}] does not equal the pod IP [{:?}]",
+            first_pod_ip,
+            status.pod_ip
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that there is exactly one status for every container.
+fn check_container_statuses(
+    description: &str,
+    containers: &[Container],
+    container_statuses: &[ContainerStatus],
+) -> Result<()> {
+    let expected_names = containers
+        .iter()
+        .map(|container| container.name.as_str())
+        .collect::<Vec<_>>();
+    let mut names = container_statuses
+        .iter()
+        .map(|container_status| container_status.name.as_str())
+        .collect::<Vec<_>>();
+
+    let mut sorted_expected_names = expected_names.to_owned();
+    sorted_expected_names.sort_unstable();
+    names.sort_unstable();
+
+    if names != sorted_expected_names {
+        Err(anyhow!(
+            "The statuses of the {} {:?} do not match the specified {} {:?}",
+            description,
+            names,
+            description,
+            expected_names
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the state of a container.
+fn check_container_state(container_status: &ContainerStatus, pod_terminated: bool) -> Result<()> {
+    let name = &container_status.name;
+
+    if let Some(state) = &container_status.state {
+        let states = [
+            state.waiting.is_some(),
+            state.running.is_some(),
+            state.terminated.is_some(),
+        ];
+        if states.iter().filter(|is_set| **is_set).count() > 1 {
+            return Err(anyhow!(
+                "The container {} is in more than one state: {:?}",
+                name,
+                state
+            ));
+        }
+
+        if container_status.ready && state.running.is_none() {
+            return Err(anyhow!("The container {} is ready but not running", name));
+        }
+    }
+
+    if pod_terminated && container_status.ready {
+        return Err(anyhow!(
+            "The container {} is ready although the pod is terminated",
+            name
+        ));
+    }
+
+    if container_status.restart_count < 0 {
+        return Err(anyhow!(
+            "The restart count of the container {} is negative",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the QoS class against the resource requirements of the
+/// containers.
+fn check_qos_class(spec: &PodSpec, qos_class: Option<&str>) -> Result<()> {
+    let qos_class = qos_class.ok_or_else(|| anyhow!("The QoS class is not set"))?;
+
+    let has_resources = spec
+        .containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+        .filter_map(|container| container.resources.as_ref())
+        .any(|resources| {
+            resources
+                .requests
+                .as_ref()
+                .map_or(false, |requests| !requests.is_empty())
+                || resources
+                    .limits
+                    .as_ref()
+                    .map_or(false, |limits| !limits.is_empty())
+        });
+
+    let valid = if has_resources {
+        qos_class == "Burstable" || qos_class == "Guaranteed"
+    } else {
+        qos_class == "BestEffort"
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "The QoS class [{}] does not match the resource requirements",
+            qos_class
+        ))
+    }
+}
+
+fn check_ip(description: &str, ip: &str) -> Result<()> {
+    ip.parse::<IpAddr>().map(|_| ()).map_err(|error| {
+        anyhow!(
+            "The {} [{}] is not a valid IP address: {}",
+            description,
+            ip,
+            error
+        )
+    })
+}
+
+/// Returns the phase of the given pod or `Unknown` if it is not set.
+#[allow(dead_code)]
+pub fn phase(pod: &Pod) -> String {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.phase.clone())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+/// Returns the status of the container with the given name.
+#[allow(dead_code)]
+pub fn container_status<'a>(pod: &'a Pod, container_name: &str) -> Option<&'a ContainerStatus> {
+    pod.status
+        .as_ref()
+        .and_then(|pod_status| pod_status.container_statuses.as_ref())
+        .and_then(|container_statuses| {
+            container_statuses
+                .iter()
+                .find(|container_status| container_status.name == container_name)
+        })
+}
+
+/// Returns the terminated state of the container with the given name.
+#[allow(dead_code)]
+pub fn terminated_state<'a>(
+    pod: &'a Pod,
+    container_name: &str,
+) -> Option<&'a ContainerStateTerminated> {
+    container_status(pod, container_name)
+        .and_then(|container_status| container_status.state.as_ref())
+        .and_then(|state| state.terminated.as_ref())
+}
+
+/// Returns true if the container with the given name is running.
+#[allow(dead_code)]
+pub fn is_running(pod: &Pod, container_name: &str) -> bool {
+    container_status(pod, container_name)
+        .and_then(|container_status| container_status.state.as_ref())
+        .and_then(|state| state.running.as_ref())
+        .is_some()
+}
+
+/// Returns the status of the init container with the given name.
+#[allow(dead_code)]
+pub fn init_container_status<'a>(
+    pod: &'a Pod,
+    container_name: &str,
+) -> Option<&'a ContainerStatus> {
+    pod.status
+        .as_ref()
+        .and_then(|pod_status| pod_status.init_container_statuses.as_ref())
+        .and_then(|container_statuses| {
+            container_statuses
+                .iter()
+                .find(|container_status| container_status.name == container_name)
+        })
+}
+
+/// Returns the status of the pod condition with the given type, i.e.
+/// `True`, `False`, or `Unknown`.
+#[allow(dead_code)]
+pub fn condition_status(pod: &Pod, condition_type: &str) -> Option<String> {
+    pod.status
+        .as_ref()
+        .and_then(|pod_status| pod_status.conditions.as_ref())
+        .and_then(|conditions| {
+            conditions
+                .iter()
+                .find(|condition| condition.type_ == condition_type)
+        })
+        .map(|condition| condition.status.to_owned())
+}
+
+/// Tolerance for timestamps reported by the agent
+///
+/// Timestamps have a resolution of one second and the clocks of the
+/// node and the test runner may differ slightly.
+const TIMESTAMP_TOLERANCE: Duration = Duration::from_secs(5);
+
+/// Validates the timestamps in the status of a pod which was created
+/// during the lifetime of the validator
+///
+/// All timestamps must lie between the creation of the validator and
+/// the validation, and they must be ordered consistently:
+///
+/// - `startTime` is set.
+/// - Every container started after `startTime`.
+/// - Every terminated container started before it finished.
+/// - A restarted container finished its last run before it started
+///   again.
+/// - Every condition has a `lastTransitionTime`.
+#[allow(dead_code)]
+pub struct TimestampValidator {
+    not_before: DateTime<Utc>,
+}
+
+impl Default for TimestampValidator {
+    fn default() -> Self {
+        TimestampValidator::new()
+    }
+}
+
+#[allow(dead_code)]
+impl TimestampValidator {
+    /// Creates a validator which accepts timestamps from now on.
+    pub fn new() -> TimestampValidator {
+        TimestampValidator {
+            not_before: Utc::now(),
+        }
+    }
+
+    /// Validates the timestamps of the given pod.
+    pub fn validate(&self, pod: &Pod) -> Result<()> {
+        let not_after = Utc::now();
+        let status = pod
+            .status
+            .as_ref()
+            .ok_or_else(|| anyhow!("The pod status is not set"))?;
+
+        let start_time = status
+            .start_time
+            .as_ref()
+            .ok_or_else(|| anyhow!("The start time of the pod is not set"))?;
+        self.check_within("startTime of the pod", start_time, &not_after)?;
+
+        let container_statuses = status
+            .init_container_statuses
+            .iter()
+            .chain(status.container_statuses.iter())
+            .flatten();
+
+        for container_status in container_statuses {
+            self.validate_container(container_status, start_time, &not_after)?;
+        }
+
+        for condition in status.conditions.iter().flatten() {
+            let last_transition_time =
+                condition.last_transition_time.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "The lastTransitionTime of the condition {} is not set",
+                        condition.type_
+                    )
+                })?;
+            self.check_within(
+                &format!("lastTransitionTime of the condition {}", condition.type_),
+                last_transition_time,
+                &not_after,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_container(
+        &self,
+        container_status: &ContainerStatus,
+        pod_start_time: &Time,
+        not_after: &DateTime<Utc>,
+    ) -> Result<()> {
+        let name = &container_status.name;
+
+        let mut started_at = None;
+
+        if let Some(state) = &container_status.state {
+            if let Some(running) = &state.running {
+                let running_since = running.started_at.as_ref().ok_or_else(|| {
+                    anyhow!("The startedAt of the running container {} is not set", name)
+                })?;
+                self.check_within(
+                    &format!("startedAt of the running container {}", name),
+                    running_since,
+                    not_after,
+                )?;
+                started_at = Some(running_since);
+            }
+            if let Some(terminated) = &state.terminated {
+                self.validate_terminated(name, terminated, not_after)?;
+                started_at = terminated.started_at.as_ref();
+            }
+        }
+
+        if let Some(started_at) = started_at {
+            check_order(
+                &format!("startTime of the pod and startedAt of container {}", name),
+                pod_start_time,
+                started_at,
+            )?;
+        }
+
+        let last_terminated = container_status
+            .last_state
+            .as_ref()
+            .and_then(|last_state| last_state.terminated.as_ref());
+
+        if let Some(last_terminated) = last_terminated {
+            self.validate_terminated(name, last_terminated, not_after)?;
+
+            if let (Some(finished_at), Some(started_at)) =
+                (last_terminated.finished_at.as_ref(), started_at)
+            {
+                check_order(
+                    &format!(
+                        "finishedAt of the last run and startedAt of the current run of \
+                        container {}",
+                        name
+                    ),
+                    finished_at,
+                    started_at,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_terminated(
+        &self,
+        name: &str,
+        terminated: &ContainerStateTerminated,
+        not_after: &DateTime<Utc>,
+    ) -> Result<()> {
+        let started_at = terminated.started_at.as_ref().ok_or_else(|| {
+            anyhow!(
+                "The startedAt of the terminated container {} is not set",
+                name
+            )
+        })?;
+        let finished_at = terminated.finished_at.as_ref().ok_or_else(|| {
+            anyhow!(
+                "The finishedAt of the terminated container {} is not set",
+                name
+            )
+        })?;
+
+        self.check_within(
+            &format!("startedAt of the terminated container {}", name),
+            started_at,
+            not_after,
+        )?;
+        self.check_within(
+            &format!("finishedAt of the terminated container {}", name),
+            finished_at,
+            not_after,
+        )?;
+        check_order(
+            &format!(
+                "startedAt and finishedAt of the terminated container {}",
+                name
+            ),
+            started_at,
+            finished_at,
+        )
+    }
+
+    /// Checks that the given timestamp lies between the creation of
+    /// this validator and the given upper bound.
+    fn check_within(
+        &self,
+        description: &str,
+        timestamp: &Time,
+        not_after: &DateTime<Utc>,
+    ) -> Result<()> {
+        let tolerance = tolerance();
+
+        if timestamp.0 + tolerance < self.not_before || timestamp.0 > *not_after + tolerance {
+            Err(anyhow!(
+                "The {} is [{}] but it must lie between [{}] and [{}]",
+                description,
+                timestamp.0,
+                self.not_before,
+                not_after
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Checks that the timestamp `earlier` does not lie after the timestamp
+/// `later`.
+fn check_order(description: &str, earlier: &Time, later: &Time) -> Result<()> {
+    if earlier.0 > later.0 + tolerance() {
+        Err(anyhow!(
+            "The {} are not ordered: [{}] is after [{}]",
+            description,
+            earlier.0,
+            later.0
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn tolerance() -> k8s_openapi::chrono::Duration {
+    k8s_openapi::chrono::Duration::from_std(TIMESTAMP_TOLERANCE)
+        .expect("TIMESTAMP_TOLERANCE is in range")
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use flate2::{write::GzEncoder, Compression};
 use integration_test_commons::test::prelude::{Container, Pod, PodSpec, Toleration};
+use k8s_openapi::api::core::v1::EnvVar;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 
 /// Package with a shell script used for testing
@@ -46,6 +47,16 @@ impl TestPackage {
         format!("{}-{}/start.sh", self.name, self.version)
     }
 
+    /// Creates a container specification for this package
+    pub fn container(&self) -> Container {
+        Container {
+            name: self.name.to_owned(),
+            image: Some(format!("{}:{}", self.name, self.version)),
+            command: Some(vec![self.command()]),
+            ..Default::default()
+        }
+    }
+
     /// Creates a pod specification for this package
     pub fn pod(&self, pod_name: &str) -> Pod {
         Pod {
@@ -54,12 +65,7 @@ impl TestPackage {
                 ..Default::default()
             },
             spec: Some(PodSpec {
-                containers: vec![Container {
-                    name: self.name.to_owned(),
-                    image: Some(format!("{}:{}", self.name, self.version)),
-                    command: Some(vec![self.command()]),
-                    ..Default::default()
-                }],
+                containers: vec![self.container()],
                 node_selector: {
                     let mut selectors = BTreeMap::new();
                     selectors.insert(
@@ -80,4 +86,64 @@ impl TestPackage {
             ..Default::default()
         }
     }
+
+    /// Creates a pod specification for this package where the given
+    /// environment variables are set on the container
+    #[allow(dead_code)]
+    pub fn pod_with_env(&self, pod_name: &str, env: &[(&str, &str)]) -> Pod {
+        let mut pod = self.pod(pod_name);
+        set_env(&mut pod, env);
+        pod
+    }
+}
+
+/// Sets the given environment variables on all containers of the given
+/// pod
+///
+/// Environment variables which were set before are replaced.
+#[allow(dead_code)]
+pub fn set_env(pod: &mut Pod, env: &[(&str, &str)]) {
+    if let Some(spec) = pod.spec.as_mut() {
+        for container in spec.containers.iter_mut() {
+            container.env = Some(env_vars(env));
+        }
+    }
+}
+
+/// Converts the given names and values into environment variables
+#[allow(dead_code)]
+pub fn env_vars(env: &[(&str, &str)]) -> Vec<EnvVar> {
+    env.iter()
+        .map(|(name, value)| EnvVar {
+            name: String::from(*name),
+            value: Some(String::from(*value)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Creates a pod specification with one container for each of the
+/// given packages
+///
+/// The containers are named after the packages. The restart policy is
+/// `Never` if all packages are jobs, otherwise `Always`.
+#[allow(dead_code)]
+pub fn multi_container_pod(pod_name: &str, packages: &[&TestPackage]) -> Pod {
+    let mut pod = packages
+        .first()
+        .expect("at least one package is required")
+        .pod(pod_name);
+
+    if let Some(spec) = pod.spec.as_mut() {
+        spec.containers = packages.iter().map(|package| package.container()).collect();
+        spec.restart_policy = Some(String::from(
+            if packages.iter().all(|package| package.job) {
+                "Never"
+            } else {
+                "Always"
+            },
+        ));
+    }
+
+    pod
 }
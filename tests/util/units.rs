@@ -0,0 +1,115 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use integration_test_commons::test::prelude::*;
+use serde::Deserialize;
+
+use super::probe::run_probe_with_env;
+use super::result::TestResult;
+use super::services::unit_timestamps_job;
+
+/// Line which precedes the JSON document of the unit-timestamps-job
+const BEGIN_MARKER: &str = "UNIT_TIMESTAMPS_BEGIN";
+
+/// Line which follows the JSON document of the unit-timestamps-job
+const END_MARKER: &str = "UNIT_TIMESTAMPS_END";
+
+/// State transition timestamps of a systemd unit printed by the
+/// unit-timestamps-job
+///
+/// The timestamps are taken from the clock of the node.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitTimestamps {
+    /// Name of the unit
+    pub unit: String,
+    /// Seconds since the Unix epoch when the unit was started
+    pub inactive_exit: Option<f64>,
+    /// Seconds since the Unix epoch when the unit became active
+    pub active_enter: Option<f64>,
+}
+
+#[allow(dead_code)]
+impl UnitTimestamps {
+    /// Parses the output of the unit-timestamps-job.
+    pub fn from_logs(lines: &[String]) -> Result<Vec<UnitTimestamps>> {
+        let document = lines
+            .iter()
+            .skip_while(|line| line.as_str() != BEGIN_MARKER)
+            .skip(1)
+            .take_while(|line| line.as_str() != END_MARKER)
+            .map(String::as_str)
+            .collect::<String>();
+
+        if document.is_empty() {
+            return Err(anyhow!(
+                "The logs do not contain unit timestamps: {:?}",
+                lines
+            ));
+        }
+
+        serde_json::from_str(&document)
+            .map_err(|error| anyhow!("The unit timestamps could not be parsed: {}", error))
+    }
+
+    /// Returns the time when the unit was started.
+    pub fn started(&self) -> Option<SystemTime> {
+        self.inactive_exit.map(system_time)
+    }
+
+    /// Returns the time when the unit became active.
+    pub fn activated(&self) -> Option<SystemTime> {
+        self.active_enter.map(system_time)
+    }
+}
+
+/// Runs the unit-timestamps-job on the given node and returns the
+/// timestamps of the only systemd unit whose name, description, or
+/// start command contains `unit_match`.
+///
+/// `None` is returned if the job could not be run, if the agent does
+/// not provide logs, or if not exactly one unit matches. In the first
+/// and last case the error is applied on `result`.
+#[allow(dead_code)]
+pub async fn unit_timestamps(
+    client: &KubeClient,
+    result: &mut TestResult,
+    node_name: &str,
+    unit_match: &str,
+) -> Option<UnitTimestamps> {
+    let job = unit_timestamps_job();
+    let job_output = run_probe_with_env(
+        client,
+        result,
+        &job,
+        node_name,
+        &[("UNIT_MATCH", unit_match)],
+    )
+    .await?;
+
+    if !job_output.succeeded {
+        result.combine::<(), _>(&Err(format!(
+            "The unit-timestamps-job failed: {:?}",
+            job_output.logs
+        )));
+    }
+
+    let parse_result = UnitTimestamps::from_logs(&job_output.logs?);
+    result.combine(&parse_result);
+
+    match parse_result.ok()?.as_slice() {
+        [unit_timestamps] => Some(unit_timestamps.to_owned()),
+        units => {
+            result.combine::<(), _>(&Err(format!(
+                "Exactly one systemd unit matching [{}] expected but found {:?}",
+                unit_match, units
+            )));
+            None
+        }
+    }
+}
+
+fn system_time(seconds_since_epoch: f64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs_f64(seconds_since_epoch)
+}